@@ -0,0 +1,269 @@
+use crate::{
+    codegen::{CodeGenerator, CodeReference, Label, VarType},
+    error::{CodeGenErrorKind, CompilationError},
+    expression::Expression,
+};
+use std::collections::HashMap;
+
+/// What an operand of a Quad instruction can be.
+#[derive(Clone, Copy)]
+enum OperandKind {
+    Target(VarType), // A variable that is assigned to
+    Source(VarType), // A variable or a literal that is read
+    Label,           // A label defined in the same asm block
+}
+
+use OperandKind::*;
+
+// The operands of the arithmetic and comparison instructions
+const INT_OPERATION: &[OperandKind] = &[
+    Target(VarType::Int),
+    Source(VarType::Int),
+    Source(VarType::Int),
+];
+const FLOAT_OPERATION: &[OperandKind] = &[
+    Target(VarType::Float),
+    Source(VarType::Float),
+    Source(VarType::Float),
+];
+const FLOAT_COMPARISON: &[OperandKind] = &[
+    Target(VarType::Int),
+    Source(VarType::Float),
+    Source(VarType::Float),
+];
+
+/// The Quad instructions and their operands, from the Quad spec.
+const INSTRUCTIONS: &[(&str, &[OperandKind])] = &[
+    ("IASN", &[Target(VarType::Int), Source(VarType::Int)]),
+    ("IPRT", &[Source(VarType::Int)]),
+    ("IINP", &[Target(VarType::Int)]),
+    ("IEQL", INT_OPERATION),
+    ("INQL", INT_OPERATION),
+    ("ILSS", INT_OPERATION),
+    ("IGRT", INT_OPERATION),
+    ("IADD", INT_OPERATION),
+    ("ISUB", INT_OPERATION),
+    ("IMLT", INT_OPERATION),
+    ("IDIV", INT_OPERATION),
+    ("RASN", &[Target(VarType::Float), Source(VarType::Float)]),
+    ("RPRT", &[Source(VarType::Float)]),
+    ("RINP", &[Target(VarType::Float)]),
+    ("REQL", FLOAT_COMPARISON),
+    ("RNQL", FLOAT_COMPARISON),
+    ("RLSS", FLOAT_COMPARISON),
+    ("RGRT", FLOAT_COMPARISON),
+    ("RADD", FLOAT_OPERATION),
+    ("RSUB", FLOAT_OPERATION),
+    ("RMLT", FLOAT_OPERATION),
+    ("RDIV", FLOAT_OPERATION),
+    ("ITOR", &[Target(VarType::Float), Source(VarType::Int)]),
+    ("RTOI", &[Target(VarType::Int), Source(VarType::Float)]),
+    ("JUMP", &[Label]),
+    ("JMPZ", &[Label, Source(VarType::Int)]),
+    ("HALT", &[]),
+];
+
+/// An operand of an instruction in an asm block, as it was written.
+/// A name is kept with its line and column, so an undefined label is reported where it's used.
+pub enum AsmOperand {
+    Name(Box<str>, (usize, usize)),
+    Literal(Expression),
+}
+
+/// A label of an asm block, whether it was defined yet, and where it was first used.
+struct AsmLabel {
+    label: Label,
+    defined: bool,
+    used_at: Option<(usize, usize)>,
+}
+
+/// An asm block being compiled, its instructions are checked against the Quad spec and copied into the generated code.
+/// The names of its variables are replaced with the names they are stored as,
+/// and its labels are replaced with new labels, so they can only be used inside of the block.
+#[derive(Default)]
+pub struct AsmBlock {
+    labels: HashMap<Box<str>, AsmLabel>,
+}
+
+impl AsmBlock {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    // Define a label at the current instruction
+    // This will generate:
+    // L{new label}:
+    pub fn gen_label_decleration(
+        &mut self,
+        name: &str,
+        codegen: &mut CodeGenerator,
+    ) -> Result<String, CodeGenErrorKind> {
+        let asm_label = self.get_label(name, codegen);
+        if asm_label.defined {
+            return Err(CodeGenErrorKind::duplicate_asm_label(name));
+        }
+        asm_label.defined = true;
+        return Ok(codegen.gen_label_decleration(asm_label.label));
+    }
+
+    // Check an instruction, and generate it with its operands replaced
+    // HALT would stop the interpreter from reading the rest of the program, so it's replaced with an exit statement.
+    // This will generate:
+    // {code of the negative constants}
+    // X a b c
+    pub fn gen_instruction(
+        &mut self,
+        instruction: &str,
+        operands: Vec<AsmOperand>,
+        codegen: &mut CodeGenerator,
+    ) -> Result<String, CodeGenErrorKind> {
+        let Some((instruction, kinds)) = INSTRUCTIONS.iter().find(|(name, _)| *name == instruction)
+        else {
+            return Err(CodeGenErrorKind::unknown_instruction(instruction));
+        };
+        if operands.len() != kinds.len() {
+            return Err(CodeGenErrorKind::wrong_operand_count(
+                instruction,
+                kinds.len(),
+                operands.len(),
+            ));
+        }
+        if *instruction == "HALT" {
+            return Ok(codegen.gen_exit_stmt());
+        }
+
+        let mut output = String::new();
+        let mut line = String::from(*instruction);
+        for (operand, kind) in operands.into_iter().zip(kinds.iter()) {
+            let operand = match (kind, operand) {
+                (Label, AsmOperand::Name(name, position)) => {
+                    let asm_label = self.get_label(&name, codegen);
+                    asm_label.used_at.get_or_insert(position);
+                    format!("{}", asm_label.label)
+                }
+                (Target(ty), AsmOperand::Name(name, _)) => {
+                    // The names the compiler generates can't be used, like in a declaration
+                    CodeGenerator::check_reserved_name(&name)?;
+                    let var_name = codegen.resolve_name(&name);
+                    if codegen.get_constant(&var_name).is_some() {
+                        return Err(CodeGenErrorKind::assignment_to_constant(&name));
+                    }
+                    Self::check_type(
+                        instruction,
+                        *kind,
+                        &name,
+                        codegen.get_var_type(&var_name)?,
+                        *ty,
+                    )?;
+                    format!("{}", var_name)
+                }
+                (Source(ty), AsmOperand::Name(name, _)) => {
+                    CodeGenerator::check_reserved_name(&name)?;
+                    let var_name = codegen.resolve_name(&name);
+                    // A constant is replaced with its value, like in an expression
+                    let value = match codegen.get_constant(&var_name) {
+                        Some(value) => value,
+                        None => {
+                            let var_type = codegen.get_var_type(&var_name)?;
+                            Expression::variable(var_name, var_type)
+                        }
+                    };
+                    Self::check_type(instruction, *kind, &name, value.ty, *ty)?;
+                    let value = value.materialize(codegen);
+                    output.push_str(&value.code_generated);
+                    format!("{}", value.code_ref)
+                }
+                (Source(ty), AsmOperand::Literal(value)) => {
+                    let literal = format!("{}", value.code_ref);
+                    Self::check_type(instruction, *kind, &literal, value.ty, *ty)?;
+                    // Quad has no negative literals
+                    let is_negative = match value.code_ref {
+                        CodeReference::IntLiteral(num) => num < 0,
+                        CodeReference::FloatLiteral(num) => num < 0.0,
+                        CodeReference::VarName(_) => false,
+                    };
+                    if is_negative {
+                        return Err(CodeGenErrorKind::negative_asm_literal(&literal));
+                    }
+                    literal
+                }
+                // A literal can't be assigned to or jumped to
+                (Target(_) | Label, AsmOperand::Literal(value)) => {
+                    return Err(CodeGenErrorKind::invalid_operand(
+                        instruction,
+                        &format!("{}", value.code_ref),
+                        Self::describe(*kind),
+                    ));
+                }
+            };
+            line.push(' ');
+            line.push_str(&operand);
+        }
+        output.push_str(&line);
+        output.push('\n');
+        return Ok(output);
+    }
+
+    /// Make sure every label that was used is defined in the block.
+    /// Returns an error for every undefined label, at its first use, in the order they were used.
+    pub fn finish(self) -> Vec<CompilationError> {
+        let mut undefined_labels: Vec<_> = self
+            .labels
+            .iter()
+            .filter(|(_, asm_label)| !asm_label.defined)
+            .filter_map(|(name, asm_label)| Some((asm_label.used_at?, name)))
+            .collect();
+        undefined_labels.sort();
+        return undefined_labels
+            .into_iter()
+            .map(|((line, column), name)| {
+                CompilationError::codegen_error(
+                    line,
+                    column,
+                    CodeGenErrorKind::undefined_asm_label(name),
+                )
+            })
+            .collect();
+    }
+
+    // The label with this name, a new label the first time the name is used
+    fn get_label(&mut self, name: &str, codegen: &mut CodeGenerator) -> &mut AsmLabel {
+        return self
+            .labels
+            .entry(Box::from(name))
+            .or_insert_with(|| AsmLabel {
+                label: codegen.new_label(),
+                defined: false,
+                used_at: None,
+            });
+    }
+
+    // An int operand accepts an int or a bool, a float operand only accepts a float
+    fn check_type(
+        instruction: &str,
+        kind: OperandKind,
+        operand: &str,
+        found: VarType,
+        expected: VarType,
+    ) -> Result<(), CodeGenErrorKind> {
+        if (found == VarType::Float) == (expected == VarType::Float) {
+            return Ok(());
+        }
+        return Err(CodeGenErrorKind::invalid_operand(
+            instruction,
+            &format!("{} with type {}", operand, found.as_str()),
+            Self::describe(kind),
+        ));
+    }
+
+    /// For printing
+    fn describe(kind: OperandKind) -> &'static str {
+        return match kind {
+            Target(VarType::Float) => "a float variable",
+            Target(_) => "an int variable",
+            Source(VarType::Float) => "a float variable or literal",
+            Source(_) => "an int variable or literal",
+            Label => "a label",
+        };
+    }
+}
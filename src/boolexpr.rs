@@ -1,5 +1,5 @@
 use crate::{
-    codegen::{CodeGenerator, CodeReference, VarType},
+    codegen::{CodeGenerator, CodeReference, Label, VarType},
     expression::{BinaryOp, Expression},
     lexer::Lexeme,
 };
@@ -10,6 +10,20 @@ pub struct BoolExpr {
     pub code_generated: String,
 }
 
+/// A boolean expression used as the condition of a statement, its code jumps to a label instead of computing a value,
+/// so every operand of && and || jumps straight to where the statement continues.
+/// The terms of the condition are separated by ||, and the factors of every term by &&.
+pub struct Condition {
+    pub terms: Vec<Vec<ConditionFactor>>,
+}
+
+/// A boolfactor of a condition, a relation is only compiled once it's known if the statement jumps when it is true or when it is false,
+/// so it can be negated by comparing with the opposite operator.
+pub enum ConditionFactor {
+    Relation(Expression, RelOp, Expression),
+    Value(BoolExpr),
+}
+
 /// Relative Operation between two Expressions
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum RelOp {
@@ -33,20 +47,52 @@ impl RelOp {
             l => panic!("Interal Error: Could not parse lexeme: {l} as RelOp"),
         };
     }
+
+    /// Compare two literals at compile time, an int literal compared with a float literal is converted to a float first.
+    /// Returns None if one of the operands isn't a literal.
+    fn fold(self, code_ref1: &CodeReference, code_ref2: &CodeReference) -> Option<bool> {
+        return match (code_ref1, code_ref2) {
+            (CodeReference::IntLiteral(a), CodeReference::IntLiteral(b)) => {
+                Some(self.compare(a, b))
+            }
+            (CodeReference::IntLiteral(a), CodeReference::FloatLiteral(b)) => {
+                Some(self.compare(&(*a as f32), b))
+            }
+            (CodeReference::FloatLiteral(a), CodeReference::IntLiteral(b)) => {
+                Some(self.compare(a, &(*b as f32)))
+            }
+            (CodeReference::FloatLiteral(a), CodeReference::FloatLiteral(b)) => {
+                Some(self.compare(a, b))
+            }
+            _ => None,
+        };
+    }
+
+    fn compare<T: PartialOrd>(self, a: &T, b: &T) -> bool {
+        return match self {
+            Self::Eq => a == b,
+            Self::NotEq => a != b,
+            Self::Less => a < b,
+            Self::LessEq => a <= b,
+            Self::Grt => a > b,
+            Self::GrtEq => a >= b,
+        };
+    }
 }
 
 impl BoolExpr {
     /// Convert the boolean expression into a regular expression.
-    /// For example, [1 < 2] will be seen as a integer expression, 0 or 1.
+    /// For example, [1 < 2] will be seen as a bool expression, 0 or 1.
     pub fn as_expression(self) -> Expression {
         return Expression {
-            ty: VarType::Int,
+            ty: VarType::Bool,
             code_ref: self.code_ref,
             code_generated: self.code_generated,
         };
     }
 
     /// Convert an expression to a boolean expression, 0 is false, 1 is true.
+    /// The expression is assumed to be a bool, other expressions should be cast to bool first.
     pub fn from_expression(expr: Expression) -> BoolExpr {
         return BoolExpr {
             code_ref: expr.code_ref,
@@ -69,41 +115,147 @@ impl BoolExpr {
     }
 
     /// `And` operation of two boolean expressions.
+    /// The second expression is only evaluated if the first one is true.
     pub fn and(
         bool_expr1: BoolExpr,
         bool_expr2: BoolExpr,
         codegen: &mut CodeGenerator,
     ) -> BoolExpr {
+        if let Some((a, b)) = Self::fold_operands(&bool_expr1, &bool_expr2, codegen) {
+            return BoolExpr::from_expression(Expression::bool_literal(a && b));
+        }
         // A boolean expression is always an Int with value 0 or 1
-        // To get a AND b we do a * b
-        // false AND false = 0 * 0 = 0
-        // false AND true = 0 * 1 = 0
-        // true AND false = 1 * 0 = 0
-        // true AND true = 1 * 1 = 1
-        return BoolExpr::from_expression(Expression::binary_op(
-            bool_expr1.as_expression(),
-            bool_expr2.as_expression(),
-            BinaryOp::Mul,
-            codegen,
-        ));
+        // If a is false, a AND b is false, so we jump straight to the "false label" without evaluating b:
+        // *a code*
+        // IASN r a
+        // JMPZ L1 r
+        // *b code*
+        // IASN r b
+        // L1: ("false label")
+        let code_ref = codegen.new_tmp_var(VarType::Bool);
+        let false_label = codegen.new_label();
+        let code_generated = format!(
+            "{}{}{}{}{}{}",
+            bool_expr1.code_generated,
+            codegen.gen_copy(VarType::Bool, &code_ref, &bool_expr1.code_ref),
+            codegen.gen_jump_if_zero(false_label, &code_ref),
+            bool_expr2.code_generated,
+            codegen.gen_copy(VarType::Bool, &code_ref, &bool_expr2.code_ref),
+            codegen.gen_label_decleration(false_label),
+        );
+        return BoolExpr {
+            code_ref,
+            code_generated,
+        };
     }
 
     /// `Or` operation of two boolean expressions.
+    /// The second expression is only evaluated if the first one is false.
     pub fn or(bool_expr1: BoolExpr, bool_expr2: BoolExpr, codegen: &mut CodeGenerator) -> BoolExpr {
+        if let Some((a, b)) = Self::fold_operands(&bool_expr1, &bool_expr2, codegen) {
+            return BoolExpr::from_expression(Expression::bool_literal(a || b));
+        }
         // A boolean expression is always an Int with value 0 or 1
-        // To get a OR b we do: not(not(a) AND not(b)) = 1 - ((1 - a) * (1 - b))
-        // false OR false = not(not(false) AND not(false)) = 1 - (1 * 1) = 0 = false
-        // false OR true = not(not(false) AND not(true)) = 1 - (1 * 0) = 1 = true
-        // true OR false = not(not(true) AND not(false)) = 1 - (0 * 1) = 1 = true
-        // true OR true = not(not(true) AND not(true)) = 1 - (0 * 0) = 1 = true
-        return BoolExpr::not(
-            BoolExpr::and(
-                BoolExpr::not(bool_expr1, codegen),
-                BoolExpr::not(bool_expr2, codegen),
-                codegen,
-            ),
-            codegen,
+        // If a is true, a OR b is true, so we jump straight to the "true label" without evaluating b:
+        // *a code*
+        // IASN r a
+        // JMPZ L1 r
+        // JUMP L2
+        // L1: ("rhs label")
+        // *b code*
+        // IASN r b
+        // L2: ("true label")
+        let code_ref = codegen.new_tmp_var(VarType::Bool);
+        let rhs_label = codegen.new_label();
+        let true_label = codegen.new_label();
+        let code_generated = format!(
+            "{}{}{}{}{}{}{}{}",
+            bool_expr1.code_generated,
+            codegen.gen_copy(VarType::Bool, &code_ref, &bool_expr1.code_ref),
+            codegen.gen_jump_if_zero(rhs_label, &code_ref),
+            codegen.gen_jump_to_label(true_label),
+            codegen.gen_label_decleration(rhs_label),
+            bool_expr2.code_generated,
+            codegen.gen_copy(VarType::Bool, &code_ref, &bool_expr2.code_ref),
+            codegen.gen_label_decleration(true_label),
         );
+        return BoolExpr {
+            code_ref,
+            code_generated,
+        };
+    }
+
+    // The values of the operands of && or ||, if they are literals and constants are folded.
+    fn fold_operands(
+        bool_expr1: &BoolExpr,
+        bool_expr2: &BoolExpr,
+        codegen: &CodeGenerator,
+    ) -> Option<(bool, bool)> {
+        if !codegen.folds_constants() {
+            return None;
+        }
+        return match (&bool_expr1.code_ref, &bool_expr2.code_ref) {
+            (CodeReference::IntLiteral(a), CodeReference::IntLiteral(b)) => {
+                Some((*a != 0, *b != 0))
+            }
+            _ => None,
+        };
+    }
+
+    /// The negation of a relation, `!(a relop b)`, compiled as a single comparison when possible:
+    /// == and != are the negation of each other, and so are < and >=, and > and <=.
+    /// Quad has no <= or >=, so !(a > b) and !(a < b) are only a single comparison when one of the operands is an int literal k,
+    /// which is replaced with k + 1 or k - 1 (!(a > k) is a < k + 1), otherwise the relation is computed and negated with `not`.
+    pub fn negated_relop(
+        expr1: Expression,
+        expr2: Expression,
+        relop: RelOp,
+        codegen: &mut CodeGenerator,
+    ) -> BoolExpr {
+        let is_int = expr1.ty.combine(expr2.ty) == VarType::Int;
+        let int_literal = |expr: &Expression| match expr.code_ref {
+            CodeReference::IntLiteral(num) if is_int => Some(num),
+            _ => None,
+        };
+        let (literal1, literal2) = (int_literal(&expr1), int_literal(&expr2));
+        return match relop {
+            RelOp::Eq => BoolExpr::relop(expr1, expr2, RelOp::NotEq, codegen),
+            RelOp::NotEq => BoolExpr::relop(expr1, expr2, RelOp::Eq, codegen),
+            RelOp::GrtEq => BoolExpr::relop(expr1, expr2, RelOp::Less, codegen),
+            RelOp::LessEq => BoolExpr::relop(expr1, expr2, RelOp::Grt, codegen),
+            // !(a > k) is a < k + 1, and !(k > b) is k - 1 < b
+            RelOp::Grt => match (
+                literal1.and_then(|k| k.checked_sub(1)),
+                literal2.and_then(|k| k.checked_add(1)),
+            ) {
+                (_, Some(k)) => {
+                    BoolExpr::relop(expr1, Expression::int_literal(k), RelOp::Less, codegen)
+                }
+                (Some(k), _) => {
+                    BoolExpr::relop(Expression::int_literal(k), expr2, RelOp::Less, codegen)
+                }
+                _ => {
+                    let greater = BoolExpr::relop(expr1, expr2, RelOp::Grt, codegen);
+                    BoolExpr::not(greater, codegen)
+                }
+            },
+            // !(a < k) is a > k - 1, and !(k < b) is k + 1 > b
+            RelOp::Less => match (
+                literal1.and_then(|k| k.checked_add(1)),
+                literal2.and_then(|k| k.checked_sub(1)),
+            ) {
+                (_, Some(k)) => {
+                    BoolExpr::relop(expr1, Expression::int_literal(k), RelOp::Grt, codegen)
+                }
+                (Some(k), _) => {
+                    BoolExpr::relop(Expression::int_literal(k), expr2, RelOp::Grt, codegen)
+                }
+                _ => {
+                    let less = BoolExpr::relop(expr1, expr2, RelOp::Less, codegen);
+                    BoolExpr::not(less, codegen)
+                }
+            },
+        };
     }
 
     /// `ReLop` operation of two expressions.
@@ -113,17 +265,21 @@ impl BoolExpr {
         relop: RelOp,
         codegen: &mut CodeGenerator,
     ) -> BoolExpr {
-        let code_ref = codegen.new_tmp_var(VarType::Int);
-        let expr_ty = expr1.ty.combine(expr2.ty);
-        match (expr1.ty, expr2.ty) {
-            (VarType::Float, VarType::Int) => {
-                expr2 = Expression::cast(VarType::Float, expr2, codegen);
-            }
-            (VarType::Int, VarType::Float) => {
-                expr1 = Expression::cast(VarType::Float, expr1, codegen);
+        if codegen.folds_constants() {
+            if let Some(result) = relop.fold(&expr1.code_ref, &expr2.code_ref) {
+                return BoolExpr::from_expression(Expression::bool_literal(result));
             }
-            _ => {}
         }
+        let code_ref = codegen.new_tmp_var(VarType::Bool);
+        let expr_ty = expr1.ty.combine(expr2.ty);
+        if expr1.ty != expr_ty {
+            expr1 = Expression::cast(expr_ty, expr1, codegen);
+        }
+        if expr2.ty != expr_ty {
+            expr2 = Expression::cast(expr_ty, expr2, codegen);
+        }
+        expr1 = expr1.materialize(codegen);
+        expr2 = expr2.materialize(codegen);
 
         let code_generated: String;
         match relop {
@@ -152,3 +308,120 @@ impl BoolExpr {
         };
     }
 }
+
+impl Condition {
+    /// Jump to the false label if the condition is false, and continue after it if it is true.
+    pub fn gen_jump_if_false(self, false_label: Label, codegen: &mut CodeGenerator) -> String {
+        // Every term but the last one jumps to the next term if one of its factors is false,
+        // and jumps over the rest of the condition if all of them are true:
+        // *factor 1 code* (assume the result is stored in variable r1)
+        // JMPZ L2 r1
+        // *factor 2 code* (assume the result is stored in variable r2)
+        // JMPZ L2 r2
+        // JUMP L1
+        // L2: ("next term label")
+        // *the factors of the last term, jumping to the false label*
+        // L1: ("true label")
+        let mut terms = self.terms;
+        let last_term = terms.pop().expect("a condition has at least one term");
+        if terms.is_empty() {
+            return Self::gen_term(last_term, false_label, codegen);
+        }
+        let true_label = codegen.new_label();
+        let mut output = String::new();
+        for term in terms {
+            let next_term_label = codegen.new_label();
+            output.push_str(&Self::gen_term(term, next_term_label, codegen));
+            output.push_str(&codegen.gen_jump_to_label(true_label));
+            output.push_str(&codegen.gen_label_decleration(next_term_label));
+        }
+        output.push_str(&Self::gen_term(last_term, false_label, codegen));
+        output.push_str(&codegen.gen_label_decleration(true_label));
+        return output;
+    }
+
+    /// Jump to the true label if the condition is true, and continue after it if it is false.
+    pub fn gen_jump_if_true(self, true_label: Label, codegen: &mut CodeGenerator) -> String {
+        // Every factor of a term but the last one jumps to the next term if it is false,
+        // and the last one jumps to the true label if it is true:
+        // *factor 1 code* (assume the result is stored in variable r1)
+        // JMPZ L1 r1
+        // *not factor 2 code* (assume the result is stored in variable t2)
+        // JMPZ Ltrue t2
+        // L1: ("next term label")
+        // *the next term*
+        let mut output = String::new();
+        for mut term in self.terms {
+            let last_factor = term.pop().expect("a term has at least one factor");
+            if term.is_empty() {
+                output.push_str(&Self::gen_jump_if_factor_true(
+                    last_factor,
+                    true_label,
+                    codegen,
+                ));
+                continue;
+            }
+            let next_term_label = codegen.new_label();
+            output.push_str(&Self::gen_term(term, next_term_label, codegen));
+            output.push_str(&Self::gen_jump_if_factor_true(
+                last_factor,
+                true_label,
+                codegen,
+            ));
+            output.push_str(&codegen.gen_label_decleration(next_term_label));
+        }
+        return output;
+    }
+
+    // Jump to the false label if one of the factors of the term is false
+    // This will generate:
+    // *factor 1 code* (assume the result is stored in variable r1)
+    // JMPZ Lfalse r1
+    // ...
+    fn gen_term(
+        term: Vec<ConditionFactor>,
+        false_label: Label,
+        codegen: &mut CodeGenerator,
+    ) -> String {
+        let mut output = String::new();
+        for factor in term {
+            let factor = match factor {
+                ConditionFactor::Relation(expr1, relop, expr2) => {
+                    BoolExpr::relop(expr1, expr2, relop, codegen)
+                }
+                ConditionFactor::Value(bool_expr) => bool_expr,
+            };
+            output.push_str(&factor.code_generated);
+            output.push_str(&codegen.gen_jump_if_false(false_label, factor));
+        }
+        return output;
+    }
+
+    // Jump to the true label if the factor is true, by jumping if its negation is false
+    // A relation is negated with the opposite operator, see BoolExpr::negated_relop, and a literal at compile time.
+    // This will generate:
+    // *not factor code* (assume the result is stored in variable t)
+    // JMPZ Ltrue t
+    fn gen_jump_if_factor_true(
+        factor: ConditionFactor,
+        true_label: Label,
+        codegen: &mut CodeGenerator,
+    ) -> String {
+        let negated_factor = match factor {
+            ConditionFactor::Relation(expr1, relop, expr2) => {
+                BoolExpr::negated_relop(expr1, expr2, relop, codegen)
+            }
+            ConditionFactor::Value(BoolExpr {
+                code_ref: CodeReference::IntLiteral(num),
+                code_generated,
+            }) => BoolExpr {
+                code_ref: CodeReference::IntLiteral((num == 0) as i32),
+                code_generated,
+            },
+            ConditionFactor::Value(bool_expr) => BoolExpr::not(bool_expr, codegen),
+        };
+        let mut output = negated_factor.code_generated.clone();
+        output.push_str(&codegen.gen_jump_if_false(true_label, negated_factor));
+        return output;
+    }
+}
@@ -2,6 +2,7 @@ use crate::{
     boolexpr::{BoolExpr, RelOp},
     error::CodeGenErrorKind,
     expression::{BinaryOp, Expression},
+    intrinsic::Intrinsic,
 };
 use std::collections::HashMap;
 
@@ -13,13 +14,23 @@ const ASSIGN_INT_COMMAND: &str = "IASN";
 const ASSIGN_FLOAT_COMMAND: &str = "RASN";
 const JUMP_COMMAND: &str = "JUMP";
 const JUMP_IF_ZERO_COMMAND: &str = "JMPZ";
+const HALT_COMMAND: &str = "HALT";
+
+/// Printed by a failed assert statement, before the line of the assert.
+pub const ASSERTION_FAILED_SENTINEL: i32 = 999999999;
+/// Printed when an array is accessed with an index that is out of range, before the line of the access.
+pub const INDEX_OUT_OF_RANGE_SENTINEL: i32 = 999999998;
+/// Printed when a recursive call is deeper than the maximum call depth, before the line of the call.
+pub const STACK_OVERFLOW_SENTINEL: i32 = 999999997;
+/// The default number of frames a recursive function can save.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 64;
 
 /// Reference an expression's result in code. For example:
 /// To compile the expression: (1 + 2) * 3
 /// The compiler will generate:
 /// t0 = 1 + 2  --  the CodeReference of (1 + 2) is t0.
 /// t1 = t0 * 3 -- the CodeReference of (1 + 2) * 3 is t1.
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub enum CodeReference {
     IntLiteral(i32),
     FloatLiteral(f32),
@@ -32,13 +43,67 @@ pub struct CodeGenerator {
     labels: usize,
     tmp_variables: usize,
     var_types: HashMap<&'static str, VarType>,
+    // The type and size of every array, each element is registered as its own variable.
+    arrays: HashMap<&'static str, (VarType, usize)>,
+    control_contexts: Vec<ControlContext>,
+    // The interpreter stops reading the program at the first HALT, so exiting early jumps to this label, right before the final HALT.
+    exit_label: Option<Label>,
+    functions: Vec<Function>,
+    // The index of the function being compiled.
+    current_function: Option<usize>,
+    // The scopes enclosing the code being compiled (the function and the blocks inside of it), the innermost scope is last.
+    scopes: Vec<Scope>,
+    // The number of blocks so far, every block stores its variables with a different prefix.
+    blocks: usize,
+    // The number of frames a recursive function can save.
+    max_call_depth: usize,
+    // The type and value of every constant, a constant is replaced by its value wherever it's used.
+    constants: HashMap<&'static str, (VarType, CodeReference)>,
+    // While compiling the value of a constant, operations between literals are computed at compile time.
+    fold_constants: bool,
+    // Set when an operation between literals couldn't be computed (an overflow or a division by zero) while folding constants.
+    fold_overflowed: bool,
+    // The code of the subroutines of the intrinsics that were called, it is placed with the code of the functions.
+    subroutines_code: String,
 }
 
 /// The type of a variable
+/// A Bool is represented in code the same way as an Int, with the value 0 (false) or 1 (true).
 #[derive(PartialEq, Eq, Clone, Copy)]
 pub enum VarType {
     Float,
     Int,
+    Bool,
+}
+
+/// The names declared in a function or a block, and the names they are stored as.
+/// A name declared in a scope is stored as "{prefix}{name}", so variables with the same name in different scopes never collide.
+struct Scope {
+    prefix: String,
+    names: HashMap<&'static str, &'static str>,
+}
+
+/// A declared function (or procedure, if it has no return type).
+/// Quad has no CALL / RET, so a call stores the index of its return site in a hidden variable and jumps to the function,
+/// which jumps back by comparing the hidden variable with the index of every return site.
+/// A function that calls itself saves its frame (parameters, locals, temporary variables and return site) on a stack before the call,
+/// and restores it after. Every frame is stored in its own copy of the variables, "{variable}__s{depth}".
+/// The frame is saved and restored by two routines of the function, which every recursive call jumps to the same way it jumps to a function.
+pub struct Function {
+    name: &'static str,
+    params: Vec<(&'static str, VarType)>,
+    return_type: Option<VarType>,
+    label: Label,
+    return_label: Label,
+    return_sites: Vec<Label>,
+    // The parameters and local variables, in the order they were declared.
+    declared_names: Vec<&'static str>,
+    // The temporary variables created for the function are the ones from this id.
+    first_tmp_var: usize,
+    // The labels of the routines that save and restore the frame, if the function calls itself.
+    frame_routines: Option<(Label, Label)>,
+    // The return sites of the two routines, and the line of the call, for every call of the function to itself.
+    recursive_calls: Vec<(Label, Label, usize)>,
 }
 
 /// An object to keep track of a label
@@ -47,16 +112,31 @@ pub struct Label {
     id: usize,
 }
 
+/// A construct that encloses the statement currently being compiled, and that a `break` statement can jump out of.
+/// Loops can also be jumped back to with a `continue` statement, and can be named so nested loops can refer to them.
+#[derive(Clone, Copy)]
+pub enum ControlContext {
+    Loop {
+        name: Option<&'static str>,
+        break_label: Label,
+        continue_label: Label,
+    },
+    Switch {
+        break_label: Label,
+    },
+}
+
 impl VarType {
     /// The type of the result of some binary operation. `self` and `other` are the types of the two operands.
     /// For example:
     /// <int> + <float> = <float>
     /// <int> + <int> = <int>
+    /// <bool> + <int> = <int>
     pub fn combine(self, other: Self) -> Self {
         use VarType::*;
         return match (self, other) {
-            (Int, Int) => Int,
-            _ => Float,
+            (Float, _) | (_, Float) => Float,
+            _ => Int,
         };
     }
 
@@ -65,17 +145,30 @@ impl VarType {
         return match self {
             VarType::Float => "float",
             VarType::Int => "int",
+            VarType::Bool => "bool",
         };
     }
 }
 
 impl CodeGenerator {
     pub fn new() -> Self {
-        return Self::default();
+        return Self {
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            ..Self::default()
+        };
+    }
+
+    /// Set the number of frames a recursive function can save.
+    pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
     }
 
     /// Get the type of a registered variable.
     pub fn get_var_type(&self, var_name: &str) -> Result<VarType, CodeGenErrorKind> {
+        // An array isn't a variable, only its elements are
+        if self.arrays.contains_key(var_name) {
+            return Err(CodeGenErrorKind::array_without_index(var_name));
+        }
         return self
             .var_types
             .get(var_name)
@@ -96,6 +189,903 @@ impl CodeGenerator {
         self.var_types.insert(var_name, ty);
     }
 
+    /// Compute operations between literals at compile time (or stop), used while compiling the value of a constant.
+    pub fn set_fold_constants(&mut self, fold_constants: bool) {
+        if fold_constants {
+            self.fold_overflowed = false;
+        }
+        self.fold_constants = fold_constants;
+    }
+
+    pub fn folds_constants(&self) -> bool {
+        return self.fold_constants;
+    }
+
+    /// Remember that an operation between literals couldn't be computed at compile time, see `check_constant`.
+    pub fn set_fold_overflowed(&mut self) {
+        self.fold_overflowed = true;
+    }
+
+    /// Register a new constant, its value is computed at compile time.
+    /// Without a value (or with an invalid one), the constant is still declared as 0, so its uses aren't reported as undeclared.
+    pub fn declare_constant(
+        &mut self,
+        name: &str,
+        ty: VarType,
+        value: Option<Expression>,
+    ) -> Result<(), CodeGenErrorKind> {
+        let value = value.map(|value| self.constant_value(name, ty, value));
+        let const_name = self.local_name(name)?;
+        let code_ref = match (&value, ty) {
+            (Some(Ok(value)), _) => value.code_ref.clone(),
+            (_, VarType::Float) => CodeReference::FloatLiteral(0.0),
+            (_, VarType::Int | VarType::Bool) => CodeReference::IntLiteral(0),
+        };
+        self.constants.insert(const_name, (ty, code_ref));
+        return match value {
+            Some(Err(codegen_err)) => Err(codegen_err),
+            _ => Ok(()),
+        };
+    }
+
+    /// Make sure the value given to `name` was computed at compile time, meaning it's a literal, and convert it to the given type.
+    /// An int (or bool) value can be converted to a float.
+    pub fn constant_value(
+        &mut self,
+        name: &str,
+        ty: VarType,
+        value: Expression,
+    ) -> Result<Expression, CodeGenErrorKind> {
+        self.check_constant(name, &value)?;
+        return match (value.ty, ty) {
+            (from, to) if from == to => Ok(value),
+            (VarType::Int | VarType::Bool, VarType::Float) => {
+                self.fold_constants = true;
+                let value = Expression::cast(ty, value, self);
+                self.fold_constants = false;
+                Ok(value)
+            }
+            _ => Err(Self::assignment_type_mismatch(name, ty, value)),
+        };
+    }
+
+    /// Make sure the value given to `name` was computed at compile time, meaning it's a literal.
+    pub fn check_constant(&self, name: &str, value: &Expression) -> Result<(), CodeGenErrorKind> {
+        if value.code_generated.is_empty() && !matches!(value.code_ref, CodeReference::VarName(_)) {
+            return Ok(());
+        }
+        if self.fold_overflowed {
+            return Err(CodeGenErrorKind::constant_overflow(name));
+        }
+        return Err(CodeGenErrorKind::not_a_constant(name));
+    }
+
+    // Initialize a declared variable, or every element of a declared array
+    // This will generate:
+    // IASN a b
+    // OR
+    // RASN a b
+    // OR
+    // IASN a__0 b
+    // IASN a__1 b
+    // ...
+    pub fn gen_initialization(&self, var_name: &str, value: &Expression) -> String {
+        let Some((ty, size)) = self.arrays.get(var_name) else {
+            return self.gen_copy(
+                value.ty,
+                &CodeReference::VarName(Box::from(var_name)),
+                &value.code_ref,
+            );
+        };
+        return (0..*size)
+            .map(|index| {
+                let element_name = Self::array_element_name(var_name, index);
+                self.gen_copy(
+                    *ty,
+                    &CodeReference::VarName(Box::from(element_name)),
+                    &value.code_ref,
+                )
+            })
+            .collect();
+    }
+
+    /// The value of a constant, or None if there is no constant with this name.
+    pub fn get_constant(&self, name: &str) -> Option<Expression> {
+        let (ty, value) = self.constants.get(name)?;
+        return Some(Expression {
+            ty: *ty,
+            code_ref: value.clone(),
+            code_generated: String::new(),
+        });
+    }
+
+    /// Register a new array, each of its elements is a variable named "{array}__{index}".
+    pub fn register_array(&mut self, array_name: &'static str, ty: VarType, size: usize) {
+        for index in 0..size {
+            let element_name = String::leak(Self::array_element_name(array_name, index));
+            self.register_variable(element_name, ty);
+        }
+        self.arrays.insert(array_name, (ty, size));
+    }
+
+    /// Get the type and size of a registered array.
+    pub fn get_array(&self, array_name: &str) -> Result<(VarType, usize), CodeGenErrorKind> {
+        return self
+            .arrays
+            .get(array_name)
+            .copied()
+            .ok_or(CodeGenErrorKind::not_an_array(array_name));
+    }
+
+    fn array_element_name(array_name: &str, index: usize) -> String {
+        return format!("{}__{}", array_name, index);
+    }
+
+    /// The variable of an array's element at a constant index
+    pub fn array_element(
+        &self,
+        array_name: &str,
+        index: i32,
+    ) -> Result<Box<str>, CodeGenErrorKind> {
+        let (_, size) = self.get_array(array_name)?;
+        if index < 0 || index as usize >= size {
+            return Err(CodeGenErrorKind::index_out_of_range(
+                array_name, index, size,
+            ));
+        }
+        return Ok(Box::from(Self::array_element_name(
+            array_name,
+            index as usize,
+        )));
+    }
+
+    // Quad has no indirect addressing, so accessing an array at a dynamic index compares the index with every possible index.
+    // `gen_element` generates the code that accesses a single element, given the element's variable.
+    // This will generate:
+    // {index code}
+    // {dispatch on the index, see gen_index_dispatch}
+    // A constant index is checked at compile time, and only generates the code for its element.
+    pub fn gen_array_access(
+        &mut self,
+        array_name: &str,
+        index: Expression,
+        line: usize,
+        mut gen_element: impl FnMut(&mut Self, &str) -> Result<String, CodeGenErrorKind>,
+    ) -> Result<String, CodeGenErrorKind> {
+        let (_, size) = self.get_array(array_name)?;
+        if index.ty == VarType::Float {
+            return Err(CodeGenErrorKind::invalid_index_type(
+                array_name,
+                index.code_ref,
+                index.ty,
+            ));
+        }
+        if let CodeReference::IntLiteral(index) = index.code_ref {
+            let element_name = self.array_element(array_name, index)?;
+            return gen_element(self, &element_name);
+        }
+
+        let mut output = index.code_generated;
+        output.push_str(&self.gen_index_dispatch(
+            &index.code_ref,
+            size,
+            (INDEX_OUT_OF_RANGE_SENTINEL, line),
+            |code_generator, i| {
+                gen_element(code_generator, &Self::array_element_name(array_name, i))
+            },
+        )?);
+        return Ok(output);
+    }
+
+    // Run the code generated by `gen_branch` for the value of the index (between 0 and size - 1), or fail with the runtime error.
+    // This will generate:
+    // INQL t0 i 0
+    // JMPZ L0 t0
+    // INQL t1 i 1
+    // JMPZ L1 t1
+    // ...
+    // IPRT {sentinel}
+    // IPRT {line}
+    // JUMP Lexit
+    // L0:
+    // {code for 0}
+    // JUMP Lpost
+    // L1:
+    // {code for 1}
+    // JUMP Lpost
+    // ...
+    // Lpost:
+    fn gen_index_dispatch(
+        &mut self,
+        index: &CodeReference,
+        size: usize,
+        (sentinel, line): (i32, usize),
+        gen_branch: impl FnMut(&mut Self, usize) -> Result<String, CodeGenErrorKind>,
+    ) -> Result<String, CodeGenErrorKind> {
+        return self.gen_dispatch(
+            index,
+            size,
+            Some(|code_generator: &mut Self| Ok(code_generator.gen_runtime_error(sentinel, line))),
+            gen_branch,
+        );
+    }
+
+    // Like gen_index_dispatch, but `gen_out_of_range` generates the code that runs when none of the indices matched.
+    // Without it the index is known to be in range, so the last index isn't compared.
+    // This will generate:
+    // INQL t0 i 0
+    // JMPZ L0 t0
+    // ...
+    // {out of range code}
+    // OR
+    // JUMP L{last}
+    // {the branches, like gen_index_dispatch}
+    fn gen_dispatch(
+        &mut self,
+        index: &CodeReference,
+        size: usize,
+        gen_out_of_range: Option<impl FnOnce(&mut Self) -> Result<String, CodeGenErrorKind>>,
+        mut gen_branch: impl FnMut(&mut Self, usize) -> Result<String, CodeGenErrorKind>,
+    ) -> Result<String, CodeGenErrorKind> {
+        let mut output = String::new();
+        let post_label = self.new_label();
+        let branch_labels: Vec<Label> = (0..size).map(|_| self.new_label()).collect();
+        let compared = match gen_out_of_range {
+            Some(_) => &branch_labels[..],
+            None => &branch_labels[..size.saturating_sub(1)],
+        };
+        for (i, branch_label) in compared.iter().enumerate() {
+            output.push_str(&self.gen_jump_if_equal(
+                VarType::Int,
+                *branch_label,
+                index,
+                &CodeReference::IntLiteral(i as i32),
+            ));
+        }
+        match (gen_out_of_range, branch_labels.last()) {
+            // None of the indices matched
+            (Some(gen_out_of_range), _) => output.push_str(&gen_out_of_range(self)?),
+            // The last index is the only one left
+            (None, Some(last_label)) => output.push_str(&self.gen_jump_to_label(*last_label)),
+            (None, None) => {}
+        }
+        for (i, branch_label) in branch_labels.into_iter().enumerate() {
+            output.push_str(&self.gen_label_decleration(branch_label));
+            output.push_str(&gen_branch(self, i)?);
+            output.push_str(&self.gen_jump_to_label(post_label));
+        }
+        output.push_str(&self.gen_label_decleration(post_label));
+        return Ok(output);
+    }
+
+    /// Declare a new function, its parameters and local variables are declared until the matching call to `end_function`.
+    pub fn declare_function(&mut self, name: &'static str) -> Result<(), CodeGenErrorKind> {
+        Self::check_reserved_name(name)?;
+        if self.find_function(name).is_ok() {
+            return Err(CodeGenErrorKind::duplicate_function(name));
+        }
+        let function = Function {
+            name,
+            params: Vec::new(),
+            return_type: None,
+            label: self.new_label(),
+            return_label: self.new_label(),
+            return_sites: Vec::new(),
+            declared_names: Vec::new(),
+            first_tmp_var: self.tmp_variables,
+            frame_routines: None,
+            recursive_calls: Vec::new(),
+        };
+        self.current_function = Some(self.functions.len());
+        self.functions.push(function);
+        self.enter_scope(format!("_{}__", name));
+        return Ok(());
+    }
+
+    /// Declare a parameter of the function being declared.
+    pub fn declare_param(&mut self, name: &str, ty: VarType) -> Result<(), CodeGenErrorKind> {
+        let var_name = self.declare_name(name)?;
+        self.register_variable(var_name, ty);
+        if let Some(function) = self.current_function {
+            self.functions[function].params.push((var_name, ty));
+        }
+        return Ok(());
+    }
+
+    /// Set the return type of the function being declared, and register the hidden variable that holds its return value.
+    pub fn declare_return_type(&mut self, ty: VarType) {
+        if let Some(function) = self.current_function {
+            self.functions[function].return_type = Some(ty);
+            let return_var = Self::function_return_var(self.functions[function].name);
+            self.register_variable(String::leak(return_var), ty);
+        }
+    }
+
+    /// The start of the function's body, generated after the body.
+    /// The frame of a recursive function is saved before it is fully computed, so its locals and temporary variables start at 0.
+    // This will generate:
+    // Lf:
+    // IASN _f__x 0 (for a recursive function)
+    // ...
+    pub fn gen_function_start(&self) -> String {
+        let Some(function) = self.current_function else {
+            return String::new();
+        };
+        let mut output = self.gen_label_decleration(self.functions[function].label);
+        if self.functions[function].frame_routines.is_none() {
+            return output;
+        }
+        let params = &self.functions[function].params;
+        for (var_name, ty) in self.frame(function) {
+            if params
+                .iter()
+                .any(|(param_name, _)| **param_name == *var_name)
+            {
+                continue;
+            }
+            let zero = match ty {
+                VarType::Float => CodeReference::FloatLiteral(0.0),
+                VarType::Int | VarType::Bool => CodeReference::IntLiteral(0),
+            };
+            output.push_str(&self.gen_copy(ty, &CodeReference::VarName(var_name), &zero));
+        }
+        return output;
+    }
+
+    /// The end of the function's body, after it the names of its parameters and locals can't be used.
+    /// `ends_with_return` is true if the body returns on every path, a function with a return type must.
+    // This will generate:
+    // JUMP Lreturn (unless the body ends with a return)
+    // {the routines that save and restore the frame, if the function calls itself}
+    pub fn end_function(&mut self, ends_with_return: bool) -> Result<String, CodeGenErrorKind> {
+        let Some(function) = self.current_function.take() else {
+            return Ok(String::new());
+        };
+        self.exit_scope();
+        let mut output = String::new();
+        if !ends_with_return {
+            if self.functions[function].return_type.is_some() {
+                return Err(CodeGenErrorKind::missing_return(
+                    self.functions[function].name,
+                ));
+            }
+            output.push_str(&self.gen_jump_to_label(self.functions[function].return_label));
+        }
+        if self.functions[function].frame_routines.is_some() {
+            output.push_str(&self.gen_frame_routines(function)?);
+        }
+        return Ok(output);
+    }
+
+    /// Enter a block, the names declared in it can only be used until the matching call to `exit_block`.
+    pub fn enter_block(&mut self) {
+        self.enter_scope(format!("__b{}__", self.blocks));
+        self.blocks += 1;
+    }
+
+    pub fn exit_block(&mut self) {
+        self.exit_scope();
+    }
+
+    fn enter_scope(&mut self, prefix: String) {
+        self.scopes.push(Scope {
+            prefix,
+            names: HashMap::new(),
+        });
+    }
+
+    fn exit_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// Return true if declaring the name in the current scope would hide a variable, an array or a constant of an enclosing scope.
+    pub fn is_shadowing(&self, name: &str) -> bool {
+        let Some((_, enclosing_scopes)) = self.scopes.split_last() else {
+            return false;
+        };
+        let var_name = enclosing_scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.names.get(name))
+            .unwrap_or(&name);
+        return self.var_types.contains_key(var_name)
+            || self.arrays.contains_key(var_name)
+            || self.constants.contains_key(var_name);
+    }
+
+    /// The name a declared variable is stored as, variables declared inside of a function are stored as "_{function}__{name}",
+    /// and variables declared inside of a block are stored as "__b{block}__{name}", which no function can be named as.
+    pub fn declare_name(&mut self, name: &str) -> Result<&'static str, CodeGenErrorKind> {
+        let var_name = self.local_name(name)?;
+        if let Some(function) = self.current_function {
+            self.functions[function].declared_names.push(var_name);
+        }
+        return Ok(var_name);
+    }
+
+    // The name a declaration is stored as, a constant is declared like a variable, but isn't part of the function's frame.
+    // A name can only be declared once in each scope, as a variable, an array or a constant.
+    fn local_name(&mut self, name: &str) -> Result<&'static str, CodeGenErrorKind> {
+        Self::check_reserved_name(name)?;
+        let Some(scope) = self.scopes.last_mut() else {
+            if self.var_types.contains_key(name)
+                || self.arrays.contains_key(name)
+                || self.constants.contains_key(name)
+            {
+                return Err(CodeGenErrorKind::duplicate_declaration(name));
+            }
+            return Ok(String::leak(String::from(name)));
+        };
+        if scope.names.contains_key(name) {
+            return Err(CodeGenErrorKind::duplicate_declaration(name));
+        }
+        let local_name = String::leak(format!("{}{}", scope.prefix, name));
+        scope
+            .names
+            .insert(String::leak(String::from(name)), local_name);
+        return Ok(local_name);
+    }
+
+    /// Make sure a declared name can't be mistaken for a name the compiler generates,
+    /// like the elements of an array, which are stored as "{array}__{index}", or the hidden variables of a function, "_{function}___site".
+    pub fn check_reserved_name(name: &str) -> Result<(), CodeGenErrorKind> {
+        if name.contains("__") || name.ends_with('_') {
+            return Err(CodeGenErrorKind::reserved_name(name));
+        }
+        return Ok(());
+    }
+
+    /// The variables saved on the stack by a recursive call, except for the site variable.
+    fn frame(&self, function: usize) -> Vec<(Box<str>, VarType)> {
+        let mut frame = Vec::new();
+        for var_name in self.functions[function].declared_names.iter() {
+            match self.arrays.get(var_name) {
+                Some((ty, size)) => frame.extend(
+                    (0..*size)
+                        .map(|index| (Box::from(Self::array_element_name(var_name, index)), *ty)),
+                ),
+                None => frame.push((Box::from(*var_name), self.var_types[var_name])),
+            }
+        }
+        for tmp_var in self.functions[function].first_tmp_var..self.tmp_variables {
+            let tmp_var_name = format!("_t{}", tmp_var);
+            let ty = self.var_types[&*tmp_var_name];
+            frame.push((Box::from(tmp_var_name), ty));
+        }
+        return frame;
+    }
+
+    // The routines that save the frame of the function on the stack before it calls itself, and restore it after.
+    // Every recursive call jumps to them with the index of its call in _f___frame_site, and they jump back like a function.
+    // The routines are shared by every call, so they save the temporary variables of the whole function.
+    // This will generate:
+    // Lsave:
+    // {dispatch on _f___depth, see gen_index_dispatch, the branch for depth d:}
+    //     IASN _f__x__s{d} _f__x
+    //     ...
+    // {if the depth is out of range:}
+    //     IPRT 999999997
+    //     {dispatch on _f___frame_site, the branch for call i:}
+    //         IPRT {line of call i}
+    //     JUMP Lexit
+    // IADD _f___depth _f___depth 1
+    // {jump back to the call, see gen_return_dispatch}
+    // Lrestore:
+    // ISUB _f___depth _f___depth 1
+    // {dispatch on _f___depth, the branch for depth d:}
+    //     IASN _f__x _f__x__s{d}
+    //     ...
+    // {jump back to the call}
+    fn gen_frame_routines(&mut self, function: usize) -> Result<String, CodeGenErrorKind> {
+        let function_name = self.functions[function].name;
+        let Some((save_label, restore_label)) = self.functions[function].frame_routines else {
+            return Ok(String::new());
+        };
+        let recursive_calls = self.functions[function].recursive_calls.clone();
+        let mut frame = self.frame(function);
+        frame.push((
+            Box::from(Self::function_site_var(function_name)),
+            VarType::Int,
+        ));
+        let depth_var = CodeReference::VarName(Box::from(Self::function_depth_var(function_name)));
+        let frame_site_var =
+            CodeReference::VarName(Box::from(Self::function_frame_site_var(function_name)));
+
+        let mut output = self.gen_label_decleration(save_label);
+        output.push_str(&self.gen_dispatch(
+            &depth_var,
+            self.max_call_depth,
+            Some(|code_generator: &mut Self| {
+                let mut output = format!("{} {}\n", OUTPUT_INT_COMMAND, STACK_OVERFLOW_SENTINEL);
+                // The line of the call that is too deep
+                output.push_str(&code_generator.gen_dispatch(
+                    &frame_site_var,
+                    recursive_calls.len(),
+                    None::<fn(&mut Self) -> Result<String, CodeGenErrorKind>>,
+                    |_, call| {
+                        Ok(format!(
+                            "{} {}\n",
+                            OUTPUT_INT_COMMAND, recursive_calls[call].2
+                        ))
+                    },
+                )?);
+                output.push_str(&code_generator.gen_exit_stmt());
+                Ok(output)
+            }),
+            |code_generator, depth| {
+                let mut output = String::new();
+                for (var_name, ty) in frame.iter() {
+                    let slot = CodeReference::VarName(Box::from(Self::frame_slot(var_name, depth)));
+                    let var = CodeReference::VarName(var_name.clone());
+                    output.push_str(&code_generator.gen_copy(*ty, &slot, &var));
+                }
+                Ok(output)
+            },
+        )?);
+        output.push_str(&self.bin_op(
+            VarType::Int,
+            BinaryOp::Add,
+            &depth_var,
+            &depth_var,
+            &CodeReference::IntLiteral(1),
+        ));
+        let saved_labels: Vec<Label> = recursive_calls.iter().map(|call| call.0).collect();
+        output.push_str(&self.gen_return_dispatch(&frame_site_var, &saved_labels));
+
+        // A frame is only restored after it was saved, so the depth is always in range
+        output.push_str(&self.gen_label_decleration(restore_label));
+        output.push_str(&self.bin_op(
+            VarType::Int,
+            BinaryOp::Sub,
+            &depth_var,
+            &depth_var,
+            &CodeReference::IntLiteral(1),
+        ));
+        output.push_str(&self.gen_dispatch(
+            &depth_var,
+            self.max_call_depth,
+            None::<fn(&mut Self) -> Result<String, CodeGenErrorKind>>,
+            |code_generator, depth| {
+                let mut output = String::new();
+                for (var_name, ty) in frame.iter() {
+                    let slot = CodeReference::VarName(Box::from(Self::frame_slot(var_name, depth)));
+                    let var = CodeReference::VarName(var_name.clone());
+                    output.push_str(&code_generator.gen_copy(*ty, &var, &slot));
+                }
+                Ok(output)
+            },
+        )?);
+        let restored_labels: Vec<Label> = recursive_calls.iter().map(|call| call.1).collect();
+        output.push_str(&self.gen_return_dispatch(&frame_site_var, &restored_labels));
+        return Ok(output);
+    }
+
+    fn frame_slot(var_name: &str, depth: usize) -> String {
+        return format!("{}__s{}", var_name, depth);
+    }
+
+    /// The name a variable used in code is stored as, a name declared in an inner scope hides the same name in the enclosing scopes.
+    pub fn resolve_name(&self, name: &str) -> Box<str> {
+        let var_name = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.names.get(name))
+            .unwrap_or(&name);
+        return Box::from(*var_name);
+    }
+
+    fn find_function(&self, name: &str) -> Result<usize, CodeGenErrorKind> {
+        return self
+            .functions
+            .iter()
+            .position(|function| function.name == name)
+            .ok_or(CodeGenErrorKind::undeclared_function(name));
+    }
+
+    // The hidden variables of a function start with "_" after the separator, so they can't be the name of a declared variable
+    fn function_return_var(function_name: &str) -> String {
+        return format!("_{}___return", function_name);
+    }
+
+    fn function_site_var(function_name: &str) -> String {
+        return format!("_{}___site", function_name);
+    }
+
+    fn function_depth_var(function_name: &str) -> String {
+        return format!("_{}___depth", function_name);
+    }
+
+    fn function_frame_site_var(function_name: &str) -> String {
+        return format!("_{}___frame_site", function_name);
+    }
+
+    // Call a function, the arguments are computed before any of them is assigned to a parameter.
+    // Returns the code of the call, and the return value if the function has one.
+    // This will generate:
+    // {arguments code}
+    // IASN _f___frame_site {index of the call} (if the function calls itself)
+    // JUMP Lsave
+    // Lsaved:
+    // IASN _f__x {argument}
+    // ...
+    // IASN _f___site {index of the return site}
+    // JUMP Lf
+    // Lsite:
+    // IASN _f___frame_site {index of the call} (if the function calls itself)
+    // JUMP Lrestore
+    // Lrestored:
+    // IASN t _f___return
+    pub fn gen_call(
+        &mut self,
+        name: &str,
+        args: Vec<Expression>,
+        line: usize,
+    ) -> Result<(String, Option<Expression>), CodeGenErrorKind> {
+        let function = match (self.find_function(name), Intrinsic::from_name(name)) {
+            (Ok(function), _) => function,
+            // A declared function hides the intrinsic with the same name
+            (Err(_), Some(intrinsic)) => {
+                let result = intrinsic.gen_call(args, line, self)?;
+                return Ok((result.code_generated.clone(), Some(result.result())));
+            }
+            (Err(err), None) => return Err(err),
+        };
+        let params = self.functions[function].params.clone();
+        if args.len() != params.len() {
+            return Err(CodeGenErrorKind::wrong_argument_count(
+                name,
+                params.len(),
+                args.len(),
+            ));
+        }
+
+        let mut output = String::new();
+        let mut arg_refs = Vec::new();
+        for (arg, (_, param_type)) in args.into_iter().zip(params.iter()) {
+            let arg = self.convert_to(*param_type, arg).map_err(|arg| {
+                CodeGenErrorKind::argument_type_mismatch(name, *param_type, arg.code_ref, arg.ty)
+            })?;
+            output.push_str(&arg.code_generated);
+            arg_refs.push(arg.code_ref);
+        }
+        let mut recursive_call = None;
+        if self.current_function == Some(function) {
+            let (save_label, restore_label) = match self.functions[function].frame_routines {
+                Some(frame_routines) => frame_routines,
+                None => {
+                    let frame_routines = (self.new_label(), self.new_label());
+                    self.functions[function].frame_routines = Some(frame_routines);
+                    frame_routines
+                }
+            };
+            let call = (self.new_label(), self.new_label(), line);
+            let frame_site =
+                CodeReference::IntLiteral(self.functions[function].recursive_calls.len() as i32);
+            self.functions[function].recursive_calls.push(call);
+            output.push_str(&self.gen_copy(
+                VarType::Int,
+                &CodeReference::VarName(Box::from(Self::function_frame_site_var(name))),
+                &frame_site,
+            ));
+            output.push_str(&self.gen_jump_to_label(save_label));
+            output.push_str(&self.gen_label_decleration(call.0));
+            recursive_call = Some((frame_site, restore_label, call.1));
+        } else if self.functions[function].frame_routines.is_some() {
+            // The function isn't running, so none of its frames are saved
+            output.push_str(&self.gen_copy(
+                VarType::Int,
+                &CodeReference::VarName(Box::from(Self::function_depth_var(name))),
+                &CodeReference::IntLiteral(0),
+            ));
+        }
+        for (arg_ref, (param_name, param_type)) in arg_refs.iter().zip(params.iter()) {
+            let param_ref = CodeReference::VarName(Box::from(*param_name));
+            output.push_str(&self.gen_copy(*param_type, &param_ref, arg_ref));
+        }
+
+        let site_label = self.new_label();
+        let callee = &mut self.functions[function];
+        let site_index = callee.return_sites.len() as i32;
+        callee.return_sites.push(site_label);
+        let (function_label, function_name, return_type) =
+            (callee.label, callee.name, callee.return_type);
+        output.push_str(&self.gen_copy(
+            VarType::Int,
+            &CodeReference::VarName(Box::from(Self::function_site_var(function_name))),
+            &CodeReference::IntLiteral(site_index),
+        ));
+        output.push_str(&self.gen_jump_to_label(function_label));
+        output.push_str(&self.gen_label_decleration(site_label));
+        if let Some((frame_site, restore_label, restored_label)) = recursive_call {
+            // The calls in between changed the index of the call
+            output.push_str(&self.gen_copy(
+                VarType::Int,
+                &CodeReference::VarName(Box::from(Self::function_frame_site_var(function_name))),
+                &frame_site,
+            ));
+            output.push_str(&self.gen_jump_to_label(restore_label));
+            output.push_str(&self.gen_label_decleration(restored_label));
+        }
+
+        let Some(return_type) = return_type else {
+            return Ok((output, None));
+        };
+        // Copy the return value, so another call to the same function won't overwrite it
+        let result = self.new_tmp_var(return_type);
+        output.push_str(&self.gen_copy(
+            return_type,
+            &result,
+            &CodeReference::VarName(Box::from(Self::function_return_var(function_name))),
+        ));
+        return Ok((
+            output,
+            Some(Expression {
+                ty: return_type,
+                code_ref: result,
+                code_generated: String::new(),
+            }),
+        ));
+    }
+
+    /// Call a subroutine that the compiler provides, it is generated the first time it is called.
+    /// The subroutine is a hidden function, `gen_body` generates its body given the variable it returns in.
+    // This will generate, the first time (into the code of the subroutines):
+    // Lf:
+    // {body}
+    // JUMP Lreturn
+    pub fn gen_subroutine_call(
+        &mut self,
+        name: &'static str,
+        params: &[(&'static str, VarType)],
+        return_type: VarType,
+        gen_body: impl FnOnce(&mut Self, &CodeReference) -> String,
+        args: Vec<Expression>,
+        line: usize,
+    ) -> Result<Expression, CodeGenErrorKind> {
+        if self.find_function(name).is_err() {
+            let function = Function {
+                name,
+                params: params.to_vec(),
+                return_type: Some(return_type),
+                label: self.new_label(),
+                return_label: self.new_label(),
+                return_sites: Vec::new(),
+                declared_names: Vec::new(),
+                first_tmp_var: self.tmp_variables,
+                frame_routines: None,
+                recursive_calls: Vec::new(),
+            };
+            let (function_label, return_label) = (function.label, function.return_label);
+            self.functions.push(function);
+            for (param_name, param_type) in params {
+                self.register_variable(param_name, *param_type);
+            }
+            let return_var = String::leak(Self::function_return_var(name));
+            self.register_variable(return_var, return_type);
+
+            let body = gen_body(self, &CodeReference::VarName(Box::from(&*return_var)));
+            let subroutine = format!(
+                "{}{}{}",
+                self.gen_label_decleration(function_label),
+                body,
+                self.gen_jump_to_label(return_label)
+            );
+            self.subroutines_code.push_str(&subroutine);
+        }
+        let (code_generated, result) = self.gen_call(name, args, line)?;
+        let result = result.expect("a subroutine always returns a value");
+        return Ok(Expression {
+            code_generated,
+            ..result
+        });
+    }
+
+    /// Take the code of the subroutines generated so far.
+    pub fn take_subroutines_code(&mut self) -> String {
+        return std::mem::take(&mut self.subroutines_code);
+    }
+
+    // Return from the function being compiled
+    // This will generate:
+    // {value code}
+    // IASN _f___return {value}
+    // JUMP Lreturn
+    pub fn gen_return_stmt(
+        &mut self,
+        value: Option<Expression>,
+    ) -> Result<String, CodeGenErrorKind> {
+        let Some(function) = self.current_function else {
+            return Err(CodeGenErrorKind::ReturnOutsideOfFunction);
+        };
+        let (function_name, return_type, return_label) = (
+            self.functions[function].name,
+            self.functions[function].return_type,
+            self.functions[function].return_label,
+        );
+        let mut output = String::new();
+        match (return_type, value) {
+            (Some(return_type), Some(value)) => {
+                let value = self.convert_to(return_type, value).map_err(|value| {
+                    CodeGenErrorKind::return_type_mismatch(
+                        function_name,
+                        Some(return_type),
+                        Some((value.code_ref, value.ty)),
+                    )
+                })?;
+                output.push_str(&value.code_generated);
+                output.push_str(&self.gen_copy(
+                    return_type,
+                    &CodeReference::VarName(Box::from(Self::function_return_var(function_name))),
+                    &value.code_ref,
+                ));
+            }
+            (None, None) => {}
+            (return_type, value) => {
+                return Err(CodeGenErrorKind::return_type_mismatch(
+                    function_name,
+                    return_type,
+                    value.map(|value| (value.code_ref, value.ty)),
+                ));
+            }
+        }
+        output.push_str(&self.gen_jump_to_label(return_label));
+        return Ok(output);
+    }
+
+    // Jump back to the return site of every function
+    // This will generate, for every function:
+    // Lreturn:
+    // {jump back to the return site, see gen_return_dispatch}
+    pub fn gen_function_returns(&mut self) -> String {
+        let mut output = String::new();
+        for function in 0..self.functions.len() {
+            let function_name = self.functions[function].name;
+            let return_sites = self.functions[function].return_sites.clone();
+            output.push_str(&self.gen_label_decleration(self.functions[function].return_label));
+            let site_var =
+                CodeReference::VarName(Box::from(Self::function_site_var(function_name)));
+            output.push_str(&self.gen_return_dispatch(&site_var, &return_sites));
+        }
+        return output;
+    }
+
+    // Jump to the return site with the index in the site variable
+    // This will generate:
+    // INQL t _f___site 0
+    // JMPZ Lsite0 t
+    // ...
+    // JUMP Lsite{last}
+    fn gen_return_dispatch(&mut self, site_var: &CodeReference, return_sites: &[Label]) -> String {
+        let mut output = String::new();
+        let Some((last_site, sites)) = return_sites.split_last() else {
+            return output;
+        };
+        for (site_index, site_label) in sites.iter().enumerate() {
+            output.push_str(&self.gen_jump_if_equal(
+                VarType::Int,
+                *site_label,
+                site_var,
+                &CodeReference::IntLiteral(site_index as i32),
+            ));
+        }
+        // The last return site is the only one left
+        output.push_str(&self.gen_jump_to_label(*last_site));
+        return output;
+    }
+
+    /// Convert an expression to the given type, an int (or bool) can be converted to a float.
+    /// Returns the expression back if it can't be converted.
+    fn convert_to(&mut self, ty: VarType, expr: Expression) -> Result<Expression, Expression> {
+        if expr.ty == ty {
+            return Ok(expr.materialize(self));
+        }
+        if ty != VarType::Float {
+            return Err(expr);
+        }
+        return Ok(Expression::cast(ty, expr, self));
+    }
+
     /// Create a new temporary variable, it's name will be "_t{id}"
     pub fn new_tmp_var(&mut self, ty: VarType) -> CodeReference {
         let tmp_var_name = String::leak(format!("_t{}", self.tmp_variables));
@@ -104,15 +1094,51 @@ impl CodeGenerator {
         return CodeReference::VarName(Box::from(&*tmp_var_name));
     }
 
-    // Generated ITOR / RTOI statements
+    // Copy b into a
+    // This will generate:
+    // IASN a b
+    // OR
+    // RASN a b
+    pub fn gen_copy(&self, ty: VarType, a: &CodeReference, b: &CodeReference) -> String {
+        return match ty {
+            VarType::Int | VarType::Bool => format!("{} {} {}\n", ASSIGN_INT_COMMAND, a, b),
+            VarType::Float => format!("{} {} {}\n", ASSIGN_FLOAT_COMMAND, a, b),
+        };
+    }
+
+    // Generated ITOR / RTOI statements, or a comparison with zero when casting to bool
     // This will generate:
     // ITOR a b
     // OR
     // RTOI a b
-    pub fn gen_cast_stmt(&self, ty: VarType, a: &CodeReference, b: &CodeReference) -> String {
-        return match ty {
-            VarType::Int => format!("RTOI {} {}\n", a, b),
-            VarType::Float => format!("ITOR {} {}\n", a, b),
+    // OR
+    // INQL a b 0
+    // OR
+    // RNQL a b 0.0
+    pub fn gen_cast_stmt(
+        &self,
+        from_ty: VarType,
+        to_ty: VarType,
+        a: &CodeReference,
+        b: &CodeReference,
+    ) -> String {
+        return match (from_ty, to_ty) {
+            (VarType::Float, VarType::Bool) => self.relop(
+                VarType::Float,
+                RelOp::NotEq,
+                a,
+                b,
+                &CodeReference::FloatLiteral(0.0),
+            ),
+            (_, VarType::Bool) => self.relop(
+                VarType::Int,
+                RelOp::NotEq,
+                a,
+                b,
+                &CodeReference::IntLiteral(0),
+            ),
+            (_, VarType::Int) => format!("RTOI {} {}\n", a, b),
+            (_, VarType::Float) => format!("ITOR {} {}\n", a, b),
         };
     }
 
@@ -130,7 +1156,7 @@ impl CodeGenerator {
     ) -> String {
         let mut op = String::new();
         match ty {
-            VarType::Int => {
+            VarType::Int | VarType::Bool => {
                 op.push_str("I");
             }
             VarType::Float => {
@@ -142,6 +1168,12 @@ impl CodeGenerator {
             BinaryOp::Div => op.push_str("DIV"),
             BinaryOp::Mul => op.push_str("MLT"),
             BinaryOp::Add => op.push_str("ADD"),
+            BinaryOp::Mod
+            | BinaryOp::BitAnd
+            | BinaryOp::BitOr
+            | BinaryOp::BitXor
+            | BinaryOp::Shl
+            | BinaryOp::Shr => unreachable!(),
         };
 
         return format!("{} {} {} {}\n", op, a, b, c);
@@ -160,7 +1192,7 @@ impl CodeGenerator {
     ) -> String {
         let mut op = String::new();
         match ty {
-            VarType::Int => {
+            VarType::Int | VarType::Bool => {
                 op.push_str("I");
             }
             VarType::Float => {
@@ -182,8 +1214,16 @@ impl CodeGenerator {
     /// Generate an output statement
     pub fn gen_output_stmt(&mut self, expr: Expression) -> Result<String, CodeGenErrorKind> {
         let mut output = String::new();
+        let mut expr = expr.materialize(self);
         // Add the code it took to generate the expression to the output
         output.push_str(&expr.code_generated);
+        if expr.ty == VarType::Bool && matches!(expr.code_ref, CodeReference::VarName(_)) {
+            // The result of a relop may be stored as the interpreter's own boolean, which is printed
+            // as True / False, so add 0 to print it as 1 / 0 like every other bool.
+            let tmp = self.new_tmp_var(VarType::Int);
+            output.push_str(&format!("IADD {} {} 0\n", tmp, expr.code_ref));
+            expr.code_ref = tmp;
+        }
         // Use the command for the matching type (IPRT / RPRT).
         match expr.code_ref {
             CodeReference::FloatLiteral(_) => output.push_str(OUTPUT_FLOAT_COMMAND),
@@ -192,7 +1232,7 @@ impl CodeGenerator {
                 let var_type = self.get_var_type(var_name)?;
                 // Use the command for the matching type (INPT / RINP).
                 match var_type {
-                    VarType::Int => output.push_str(OUTPUT_INT_COMMAND),
+                    VarType::Int | VarType::Bool => output.push_str(OUTPUT_INT_COMMAND),
                     VarType::Float => output.push_str(OUTPUT_FLOAT_COMMAND),
                 }
             }
@@ -207,11 +1247,16 @@ impl CodeGenerator {
         let var_type = self.get_var_type(&var_name)?;
         // Use the command for the matching type (INPT / RINP).
         match var_type {
-            VarType::Int => output.push_str(INPUT_INT_COMMAND),
+            VarType::Int | VarType::Bool => output.push_str(INPUT_INT_COMMAND),
             VarType::Float => output.push_str(INPUT_FLOAT_COMMAND),
         }
         // The command takes the variable name as the only argument.
         output.push_str(&format!(" {}\n", var_name));
+        // Any non zero input is true, make sure a bool variable is always 0 or 1.
+        if var_type == VarType::Bool {
+            let var_ref = CodeReference::VarName(Box::from(var_name));
+            output.push_str(&self.gen_cast_stmt(VarType::Int, VarType::Bool, &var_ref, &var_ref));
+        }
         return Ok(output);
     }
 
@@ -224,24 +1269,78 @@ impl CodeGenerator {
         let var_type = self.get_var_type(&var_name)?;
         // Return an error if there is a type mismatch
         if expr.ty != var_type {
-            return Err(CodeGenErrorKind::type_mismtach(
-                CodeReference::VarName(Box::from(var_name)),
-                var_type,
-                expr.code_ref,
-                expr.ty,
-            ));
+            return Err(Self::assignment_type_mismatch(var_name, var_type, expr));
         }
+        let expr = expr.materialize(self);
         // Push all the code it tool to generate the expression before the assignment statement
         output.push_str(&expr.code_generated);
         // Use the command for the matching type (IASN / RASN).
         match var_type {
-            VarType::Int => output.push_str(ASSIGN_INT_COMMAND),
+            VarType::Int | VarType::Bool => output.push_str(ASSIGN_INT_COMMAND),
             VarType::Float => output.push_str(ASSIGN_FLOAT_COMMAND),
         }
         output.push_str(&format!(" {} {}\n", var_name, expr.code_ref));
         return Ok(output);
     }
 
+    /// Generate a compound assignment (+=, -=, *=, /=), the result is put directly into the variable:
+    /// IADD a a b
+    /// OR
+    /// RADD a a b
+    /// (and the same for ISUB / IMLT / IDIV / RSUB / RMLT / RDIV)
+    pub fn gen_compound_assignment_stmt(
+        &mut self,
+        var_name: &str,
+        binop: BinaryOp,
+        expr: Expression,
+    ) -> Result<String, CodeGenErrorKind> {
+        let mut output = String::new();
+        let var_type = self.get_var_type(&var_name)?;
+        // a += b is the same as a = a + b, so the type of a + b has to be the type of a.
+        if var_type == VarType::Bool || var_type.combine(expr.ty) != var_type {
+            return Err(Self::assignment_type_mismatch(var_name, var_type, expr));
+        }
+        let mut expr = expr;
+        if expr.ty != var_type {
+            expr = Expression::cast(var_type, expr, self);
+        }
+        let expr = expr.materialize(self);
+        // Push all the code it took to generate the expression before the assignment statement
+        output.push_str(&expr.code_generated);
+        let var_ref = CodeReference::VarName(Box::from(var_name));
+        output.push_str(&self.bin_op(var_type, binop, &var_ref, &var_ref, &expr.code_ref));
+        return Ok(output);
+    }
+
+    /// Generate an increment (a++) or a decrement (a--) statement:
+    /// IADD a a 1
+    /// OR
+    /// RSUB a a 1.0
+    pub fn gen_increment_stmt(
+        &mut self,
+        var_name: &str,
+        binop: BinaryOp,
+    ) -> Result<String, CodeGenErrorKind> {
+        let one = match self.get_var_type(&var_name)? {
+            VarType::Float => Expression::float_literal(1.0),
+            VarType::Int | VarType::Bool => Expression::int_literal(1),
+        };
+        return self.gen_compound_assignment_stmt(var_name, binop, one);
+    }
+
+    fn assignment_type_mismatch(
+        var_name: &str,
+        var_type: VarType,
+        expr: Expression,
+    ) -> CodeGenErrorKind {
+        return CodeGenErrorKind::type_mismtach(
+            CodeReference::VarName(Box::from(var_name)),
+            var_type,
+            expr.code_ref,
+            expr.ty,
+        );
+    }
+
     /// Register a new label, and return a struct to identify it.
     pub fn new_label(&mut self) -> Label {
         self.labels += 1;
@@ -250,6 +1349,142 @@ impl CodeGenerator {
         };
     }
 
+    /// Enter a construct that can be exited with `break`, until the matching call to `exit_control_context`.
+    pub fn enter_control_context(&mut self, context: ControlContext) {
+        self.control_contexts.push(context);
+    }
+
+    /// Exit the innermost construct that was entered with `enter_control_context`.
+    pub fn exit_control_context(&mut self) {
+        self.control_contexts.pop();
+    }
+
+    /// Return an error if a loop with this name is already enclosing the statement currently being compiled.
+    pub fn check_loop_name(&self, loop_name: &str) -> Result<(), CodeGenErrorKind> {
+        if self.find_named_loop(loop_name).is_ok() {
+            return Err(CodeGenErrorKind::shadowed_loop_label(loop_name));
+        }
+        return Ok(());
+    }
+
+    /// Get the (break label, continue label) of the enclosing loop with this name.
+    fn find_named_loop(&self, loop_name: &str) -> Result<(Label, Label), CodeGenErrorKind> {
+        return self
+            .control_contexts
+            .iter()
+            .rev()
+            .find_map(|context| match context {
+                ControlContext::Loop {
+                    name: Some(name),
+                    break_label,
+                    continue_label,
+                } if *name == loop_name => Some((*break_label, *continue_label)),
+                _ => None,
+            })
+            .ok_or(CodeGenErrorKind::unknown_loop_label(loop_name));
+    }
+
+    // Break out of the innermost loop / switch statement, or out of the enclosing loop with the given name
+    // This will generate:
+    // JUMP L
+    // L being the label after the loop / switch statement
+    pub fn gen_break_stmt(&self, loop_name: Option<&str>) -> Result<String, CodeGenErrorKind> {
+        if let Some(loop_name) = loop_name {
+            let (break_label, _) = self.find_named_loop(loop_name)?;
+            return Ok(self.gen_jump_to_label(break_label));
+        }
+        return match self.control_contexts.last() {
+            Some(ControlContext::Loop { break_label, .. })
+            | Some(ControlContext::Switch { break_label }) => {
+                Ok(self.gen_jump_to_label(*break_label))
+            }
+            None => Err(CodeGenErrorKind::BreakOutsideOfContext),
+        };
+    }
+
+    // Continue to the next iteration of the innermost loop (switch statements are skipped), or of the enclosing loop with the given name
+    // This will generate:
+    // JUMP L
+    // L being the label before the condition of the loop
+    pub fn gen_continue_stmt(&self, loop_name: Option<&str>) -> Result<String, CodeGenErrorKind> {
+        if let Some(loop_name) = loop_name {
+            let (_, continue_label) = self.find_named_loop(loop_name)?;
+            return Ok(self.gen_jump_to_label(continue_label));
+        }
+        return self
+            .control_contexts
+            .iter()
+            .rev()
+            .find_map(|context| match context {
+                ControlContext::Loop { continue_label, .. } => Some(*continue_label),
+                ControlContext::Switch { .. } => None,
+            })
+            .map(|continue_label| self.gen_jump_to_label(continue_label))
+            .ok_or(CodeGenErrorKind::ContinueOutsideOfLoop);
+    }
+
+    // Stop the program
+    // This will generate:
+    // JUMP L
+    // L being the label before the final HALT
+    pub fn gen_exit_stmt(&mut self) -> String {
+        let exit_label = match self.exit_label {
+            Some(exit_label) => exit_label,
+            None => {
+                let exit_label = self.new_label();
+                self.exit_label = Some(exit_label);
+                exit_label
+            }
+        };
+        return self.gen_jump_to_label(exit_label);
+    }
+
+    // Print the sentinel and the line of the assert, and stop the program if the condition is false
+    // This will generate:
+    // {boolexpr code}
+    // ISUB t 1 r
+    // JMPZ L t
+    // IPRT 999999999
+    // IPRT {line}
+    // JUMP Lexit
+    // L:
+    pub fn gen_assert_stmt(&mut self, boolexpr: BoolExpr, line: usize) -> String {
+        let mut output = String::new();
+        let ok_label = self.new_label();
+        let failed = BoolExpr::not(boolexpr, self);
+        output.push_str(&failed.code_generated);
+        output.push_str(&self.gen_jump_if_zero(ok_label, &failed.code_ref));
+        output.push_str(&self.gen_runtime_error(ASSERTION_FAILED_SENTINEL, line));
+        output.push_str(&self.gen_label_decleration(ok_label));
+        return output;
+    }
+
+    // Print the sentinel of the error and the line it happened in, and stop the program
+    // This will generate:
+    // IPRT {sentinel}
+    // IPRT {line}
+    // JUMP Lexit
+    fn gen_runtime_error(&mut self, sentinel: i32, line: usize) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("{} {}\n", OUTPUT_INT_COMMAND, sentinel));
+        output.push_str(&format!("{} {}\n", OUTPUT_INT_COMMAND, line));
+        output.push_str(&self.gen_exit_stmt());
+        return output;
+    }
+
+    // The end of the program, with the label exit statements jump to if there are any
+    // This will generate:
+    // Lexit:
+    // HALT
+    pub fn gen_halt(&self) -> String {
+        let mut output = String::new();
+        if let Some(exit_label) = self.exit_label {
+            output.push_str(&self.gen_label_decleration(exit_label));
+        }
+        output.push_str(HALT_COMMAND);
+        return output;
+    }
+
     pub fn gen_label_decleration(&self, label: Label) -> String {
         return format!("L{}:\n", label.id);
     }
@@ -259,9 +1494,41 @@ impl CodeGenerator {
     }
 
     pub fn gen_jump_if_false(&self, label: Label, boolexpr: BoolExpr) -> String {
+        return self.gen_jump_if_zero(label, &boolexpr.code_ref);
+    }
+
+    // JMPZ only takes a variable, so the jump is resolved at compile time for a literal (true / false)
+    pub fn gen_jump_if_zero(&self, label: Label, a: &CodeReference) -> String {
+        return match a {
+            CodeReference::IntLiteral(0) => self.gen_jump_to_label(label),
+            CodeReference::IntLiteral(_) | CodeReference::FloatLiteral(_) => String::new(),
+            CodeReference::VarName(_) => {
+                format!("{} L{} {}\n", JUMP_IF_ZERO_COMMAND, label.id, a)
+            }
+        };
+    }
+
+    // Jump to the label if a == b
+    // This will generate:
+    // INQL t a b
+    // JMPZ L t
+    // OR
+    // RNQL t a b
+    // JMPZ L t
+    pub fn gen_jump_if_equal(
+        &mut self,
+        ty: VarType,
+        label: Label,
+        a: &CodeReference,
+        b: &CodeReference,
+    ) -> String {
+        let not_equal = self.new_tmp_var(VarType::Int);
         return format!(
-            "{} L{} {}\n",
-            JUMP_IF_ZERO_COMMAND, label.id, boolexpr.code_ref
+            "{}{} L{} {}\n",
+            self.relop(ty, RelOp::NotEq, &not_equal, a, b),
+            JUMP_IF_ZERO_COMMAND,
+            label.id,
+            not_equal
         );
     }
 }
@@ -270,12 +1537,20 @@ impl std::fmt::Display for CodeReference {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             CodeReference::IntLiteral(lit) => write!(f, "{}", lit),
-            CodeReference::FloatLiteral(lit) => write!(f, "{:?}", lit),
+            // Quad doesn't accept exponents, and a float always has a decimal point
+            CodeReference::FloatLiteral(lit) if lit.fract() == 0.0 => write!(f, "{:.1}", lit),
+            CodeReference::FloatLiteral(lit) => write!(f, "{}", lit),
             CodeReference::VarName(var_name) => write!(f, "{}", var_name),
         }
     }
 }
 
+impl std::fmt::Display for Label {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "L{}", self.id)
+    }
+}
+
 impl std::fmt::Display for VarType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.as_str())
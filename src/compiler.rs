@@ -1,19 +1,42 @@
-use crate::{lexer::Lexer, parser::Parser};
+use crate::{codegen::DEFAULT_MAX_CALL_DEPTH, lexer::Lexer, parser::Parser};
 
 pub struct Compiler {
     source_code: String,
+    max_call_depth: usize,
 }
 
 impl Compiler {
     /// Initiallize a the compiler with source code.
     pub fn init(source_code: String) -> Compiler {
-        return Compiler { source_code };
+        return Compiler {
+            source_code,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+        };
+    }
+
+    /// Set the number of frames a recursive function can save before the program stops.
+    pub fn with_max_call_depth(mut self, max_call_depth: usize) -> Compiler {
+        self.max_call_depth = max_call_depth;
+        return self;
     }
 
     /// Compile the source code, output a string
     pub fn compile(self) -> Option<String> {
-        let parser = Parser::new(Lexer::lex_tokens(self.source_code));
-        return parser.parse_program().map_or_else(
+        let (tokens, lexing_errors) = Lexer::lex(self.source_code);
+        let mut parser = Parser::new(tokens);
+        parser
+            .code_generator
+            .set_max_call_depth(self.max_call_depth);
+        let mut output = parser.parse_program();
+        for warning in parser.warnings() {
+            eprintln!("{}", warning);
+        }
+        // The lexing errors come before the errors they caused while parsing
+        if !lexing_errors.is_empty() {
+            let parsing_errors = output.err().unwrap_or_default();
+            output = Err(lexing_errors.into_iter().chain(parsing_errors).collect());
+        }
+        return output.map_or_else(
             // If there are errors, print all of them, and return None
             |errors| {
                 for error in errors {
@@ -16,6 +16,118 @@ pub enum CodeGenErrorKind {
         found_ref: CodeReference,
         found_type: VarType,
     },
+    TypeMismatchInCase {
+        scrutinee_type: VarType,
+        case_ref: CodeReference,
+        case_type: VarType,
+    },
+    DuplicateCase {
+        case_ref: CodeReference,
+    },
+    BreakOutsideOfContext,
+    ContinueOutsideOfLoop,
+    UnknownLoopLabel {
+        name: String,
+    },
+    ShadowedLoopLabel {
+        name: String,
+    },
+    InvalidOperandType {
+        operator: &'static str,
+        operand_ref: CodeReference,
+        operand_type: VarType,
+    },
+    NotAnArray {
+        name: String,
+    },
+    ArrayWithoutIndex {
+        name: String,
+    },
+    InvalidArraySize {
+        name: String,
+        size_ref: CodeReference,
+    },
+    InvalidIndexType {
+        array: String,
+        index_ref: CodeReference,
+        index_type: VarType,
+    },
+    IndexOutOfRange {
+        array: String,
+        index: i32,
+        size: usize,
+    },
+    UndeclaredFunction {
+        name: String,
+    },
+    DuplicateFunction {
+        name: String,
+    },
+    WrongArgumentCount {
+        function: String,
+        expected: usize,
+        found: usize,
+    },
+    TypeMismatchInArgument {
+        function: String,
+        expected_type: VarType,
+        found_ref: CodeReference,
+        found_type: VarType,
+    },
+    TypeMismatchInReturn {
+        function: String,
+        expected_type: Option<VarType>,
+        found: Option<(CodeReference, VarType)>,
+    },
+    ReturnOutsideOfFunction,
+    ProcedureInExpression {
+        name: String,
+    },
+    MissingReturn {
+        function: String,
+    },
+    NotAConstant {
+        name: String,
+    },
+    ConstantOverflow {
+        name: String,
+    },
+    AssignmentToConstant {
+        name: String,
+    },
+    DuplicateDeclaration {
+        name: String,
+    },
+    ReservedName {
+        name: String,
+    },
+    UnknownInstruction {
+        name: String,
+    },
+    WrongOperandCount {
+        instruction: String,
+        expected: usize,
+        found: usize,
+    },
+    InvalidOperand {
+        instruction: String,
+        operand: String,
+        expected: &'static str,
+    },
+    UndefinedAsmLabel {
+        name: String,
+    },
+    DuplicateAsmLabel {
+        name: String,
+    },
+    NegativeAsmLiteral {
+        literal: String,
+    },
+}
+
+/// Something suspicious in the program, that doesn't stop the compilation
+pub enum CodeGenWarningKind {
+    ShadowedVariable { name: String },
 }
 
 /// An error that has occured during the parsing process
@@ -25,16 +137,31 @@ pub enum ParsingErrorKind {
         expected: Box<[Token]>,
         found: Token,
     },
+    InvalidNumber {
+        literal: String,
+        reason: &'static str,
+    },
 }
 
 /// An error that has occured during the Lexing process
-pub enum LexingErrorKind {}
+pub enum LexingErrorKind {
+    UnterminatedComment,
+    UnmatchedCommentEnd,
+}
 
 /// An instance of a compilation error
 pub struct CompilationError {
     line: usize,
     column: usize,
     err_kind: CompilationErrorKind,
+    context: Option<&'static str>,
+}
+
+/// An instance of a compilation warning
+pub struct CompilationWarning {
+    line: usize,
+    column: usize,
+    warning_kind: CodeGenWarningKind,
 }
 
 pub enum CompilationErrorKind {
@@ -50,6 +177,16 @@ impl CompilationError {
             line,
             column,
             err_kind: CompilationErrorKind::ParsingError(err_kind),
+            context: None,
+        };
+    }
+
+    pub fn lexing_error(line: usize, column: usize, err_kind: LexingErrorKind) -> Self {
+        return Self {
+            line,
+            column,
+            err_kind: CompilationErrorKind::LexingError(err_kind),
+            context: None,
         };
     }
 
@@ -58,6 +195,7 @@ impl CompilationError {
             line,
             column,
             err_kind: CompilationErrorKind::CodeGenError(err_kind),
+            context: None,
         };
     }
 
@@ -66,6 +204,7 @@ impl CompilationError {
             line: 0,
             column: 0,
             err_kind: CompilationErrorKind::InternalError(desc),
+            context: None,
         };
     }
 
@@ -74,6 +213,31 @@ impl CompilationError {
             line: 999,
             column: 999,
             err_kind: CompilationErrorKind::ParsingError(ParsingErrorKind::UnexpectedEOF),
+            context: None,
+        };
+    }
+
+    /// Describe the part of the program that the error occured in, for example: "the initialization of a for loop".
+    pub fn in_context(mut self, context: &'static str) -> Self {
+        self.context = Some(context);
+        return self;
+    }
+}
+
+impl CompilationWarning {
+    pub fn codegen_warning(line: usize, column: usize, warning_kind: CodeGenWarningKind) -> Self {
+        return Self {
+            line,
+            column,
+            warning_kind,
+        };
+    }
+}
+
+impl CodeGenWarningKind {
+    pub fn shadowed_variable(name: &str) -> Self {
+        return Self::ShadowedVariable {
+            name: String::from(name),
         };
     }
 }
@@ -99,6 +263,209 @@ impl CodeGenErrorKind {
             found_type,
         };
     }
+
+    pub fn case_type_mismatch(
+        scrutinee_type: VarType,
+        case_ref: CodeReference,
+        case_type: VarType,
+    ) -> Self {
+        return CodeGenErrorKind::TypeMismatchInCase {
+            scrutinee_type,
+            case_ref,
+            case_type,
+        };
+    }
+
+    pub fn duplicate_case(case_ref: CodeReference) -> Self {
+        return CodeGenErrorKind::DuplicateCase { case_ref };
+    }
+
+    pub fn unknown_loop_label(name: &str) -> Self {
+        return CodeGenErrorKind::UnknownLoopLabel {
+            name: String::from(name),
+        };
+    }
+
+    pub fn shadowed_loop_label(name: &str) -> Self {
+        return CodeGenErrorKind::ShadowedLoopLabel {
+            name: String::from(name),
+        };
+    }
+
+    pub fn not_an_array(name: &str) -> Self {
+        return CodeGenErrorKind::NotAnArray {
+            name: String::from(name),
+        };
+    }
+
+    pub fn array_without_index(name: &str) -> Self {
+        return CodeGenErrorKind::ArrayWithoutIndex {
+            name: String::from(name),
+        };
+    }
+
+    pub fn invalid_array_size(name: &str, size_ref: CodeReference) -> Self {
+        return CodeGenErrorKind::InvalidArraySize {
+            name: String::from(name),
+            size_ref,
+        };
+    }
+
+    pub fn invalid_index_type(array: &str, index_ref: CodeReference, index_type: VarType) -> Self {
+        return CodeGenErrorKind::InvalidIndexType {
+            array: String::from(array),
+            index_ref,
+            index_type,
+        };
+    }
+
+    pub fn index_out_of_range(array: &str, index: i32, size: usize) -> Self {
+        return CodeGenErrorKind::IndexOutOfRange {
+            array: String::from(array),
+            index,
+            size,
+        };
+    }
+
+    pub fn undeclared_function(name: &str) -> Self {
+        return CodeGenErrorKind::UndeclaredFunction {
+            name: String::from(name),
+        };
+    }
+
+    pub fn duplicate_function(name: &str) -> Self {
+        return CodeGenErrorKind::DuplicateFunction {
+            name: String::from(name),
+        };
+    }
+
+    pub fn wrong_argument_count(function: &str, expected: usize, found: usize) -> Self {
+        return CodeGenErrorKind::WrongArgumentCount {
+            function: String::from(function),
+            expected,
+            found,
+        };
+    }
+
+    pub fn argument_type_mismatch(
+        function: &str,
+        expected_type: VarType,
+        found_ref: CodeReference,
+        found_type: VarType,
+    ) -> Self {
+        return CodeGenErrorKind::TypeMismatchInArgument {
+            function: String::from(function),
+            expected_type,
+            found_ref,
+            found_type,
+        };
+    }
+
+    pub fn return_type_mismatch(
+        function: &str,
+        expected_type: Option<VarType>,
+        found: Option<(CodeReference, VarType)>,
+    ) -> Self {
+        return CodeGenErrorKind::TypeMismatchInReturn {
+            function: String::from(function),
+            expected_type,
+            found,
+        };
+    }
+
+    pub fn procedure_in_expression(name: &str) -> Self {
+        return CodeGenErrorKind::ProcedureInExpression {
+            name: String::from(name),
+        };
+    }
+
+    pub fn missing_return(function: &str) -> Self {
+        return CodeGenErrorKind::MissingReturn {
+            function: String::from(function),
+        };
+    }
+
+    pub fn not_a_constant(name: &str) -> Self {
+        return CodeGenErrorKind::NotAConstant {
+            name: String::from(name),
+        };
+    }
+
+    pub fn constant_overflow(name: &str) -> Self {
+        return CodeGenErrorKind::ConstantOverflow {
+            name: String::from(name),
+        };
+    }
+
+    pub fn assignment_to_constant(name: &str) -> Self {
+        return CodeGenErrorKind::AssignmentToConstant {
+            name: String::from(name),
+        };
+    }
+
+    pub fn duplicate_declaration(name: &str) -> Self {
+        return CodeGenErrorKind::DuplicateDeclaration {
+            name: String::from(name),
+        };
+    }
+
+    pub fn reserved_name(name: &str) -> Self {
+        return CodeGenErrorKind::ReservedName {
+            name: String::from(name),
+        };
+    }
+
+    pub fn unknown_instruction(name: &str) -> Self {
+        return CodeGenErrorKind::UnknownInstruction {
+            name: String::from(name),
+        };
+    }
+
+    pub fn wrong_operand_count(instruction: &str, expected: usize, found: usize) -> Self {
+        return CodeGenErrorKind::WrongOperandCount {
+            instruction: String::from(instruction),
+            expected,
+            found,
+        };
+    }
+
+    pub fn invalid_operand(instruction: &str, operand: &str, expected: &'static str) -> Self {
+        return CodeGenErrorKind::InvalidOperand {
+            instruction: String::from(instruction),
+            operand: String::from(operand),
+            expected,
+        };
+    }
+
+    pub fn undefined_asm_label(name: &str) -> Self {
+        return CodeGenErrorKind::UndefinedAsmLabel {
+            name: String::from(name),
+        };
+    }
+
+    pub fn duplicate_asm_label(name: &str) -> Self {
+        return CodeGenErrorKind::DuplicateAsmLabel {
+            name: String::from(name),
+        };
+    }
+
+    pub fn negative_asm_literal(literal: &str) -> Self {
+        return CodeGenErrorKind::NegativeAsmLiteral {
+            literal: String::from(literal),
+        };
+    }
+
+    pub fn invalid_operand_type(
+        operator: &'static str,
+        operand_ref: CodeReference,
+        operand_type: VarType,
+    ) -> Self {
+        return CodeGenErrorKind::InvalidOperandType {
+            operator,
+            operand_ref,
+            operand_type,
+        };
+    }
 }
 
 impl ParsingErrorKind {
@@ -108,6 +475,13 @@ impl ParsingErrorKind {
             found,
         };
     }
+
+    pub fn invalid_number(literal: &str, reason: &'static str) -> Self {
+        return Self::InvalidNumber {
+            literal: String::from(literal),
+            reason,
+        };
+    }
 }
 
 impl Display for ParsingErrorKind {
@@ -116,6 +490,9 @@ impl Display for ParsingErrorKind {
             ParsingErrorKind::UnexpectedEOF => {
                 write!(f, "Unexpected EOF Error\n    Unexpected reach of EOF")
             }
+            ParsingErrorKind::InvalidNumber { literal, reason } => {
+                write!(f, "Invalid Number Error\n    The number {} is malformed: {}\n    Fix this error by writing a number like 42, 0x2A, 0b101010, 1_000, 4.2, .5, 4.2e-1, 42i or 42f.", literal, reason)
+            }
             ParsingErrorKind::UnexpectedToken { expected, found } => {
                 write!(
                     f,
@@ -156,13 +533,168 @@ impl Display for CodeGenErrorKind {
                 write!(f, "Provided Incorrect type in Assignment Error\n    Expected type {} because {} has type {}\n    But found {} with type {}\n    Fix this error by casting {} to {} using static_cast<{}>.",
                     expected_type, expected_ref, expected_type, found_ref, found_type, found_ref, expected_type, expected_type)
             }
+            CodeGenErrorKind::TypeMismatchInCase {
+                scrutinee_type,
+                case_ref,
+                case_type,
+            } => {
+                write!(f, "Provided Incorrect type in Case Error\n    Expected a case of type {} because the switch expression has type {}\n    But found case {} with type {}\n    Fix this error by writing the case as a {} literal.",
+                    scrutinee_type, scrutinee_type, case_ref, case_type, scrutinee_type)
+            }
+            CodeGenErrorKind::DuplicateCase { case_ref } => {
+                write!(f, "Duplicate Case Error\n    The case {} appears more than once in the same switch statement\n    Fix this error by removing one of the duplicate cases.", case_ref)
+            }
+            CodeGenErrorKind::BreakOutsideOfContext => {
+                write!(f, "Break Outside of Loop or Switch Error\n    A break statement can only appear inside of a loop or a switch statement\n    Fix this error by removing the break statement.")
+            }
+            CodeGenErrorKind::ContinueOutsideOfLoop => {
+                write!(f, "Continue Outside of Loop Error\n    A continue statement can only appear inside of a loop\n    Fix this error by removing the continue statement.")
+            }
+            CodeGenErrorKind::UnknownLoopLabel { name } => {
+                write!(f, "Unknown Loop Label Error\n    There is no enclosing loop with the label: {}\n    Fix this error by labeling one of the enclosing loops with {}:", name, name)
+            }
+            CodeGenErrorKind::ShadowedLoopLabel { name } => {
+                write!(f, "Shadowed Loop Label Error\n    The label {} is already used by an enclosing loop\n    Fix this error by renaming one of the loops.", name)
+            }
+            CodeGenErrorKind::InvalidOperandType {
+                operator,
+                operand_ref,
+                operand_type,
+            } => {
+                write!(f, "Provided Incorrect type for Operator Error\n    The operator {} only accepts operands of type int\n    But found {} with type {}\n    Fix this error by casting {} to int using static_cast<int>.",
+                    operator, operand_ref, operand_type, operand_ref)
+            }
+            CodeGenErrorKind::NotAnArray { name } => {
+                write!(f, "Not an Array Error\n    {} is indexed, but it is not declared as an array\n    Fix this error by declaring {} as an array, for example: {}[10]: int;", name, name, name)
+            }
+            CodeGenErrorKind::ArrayWithoutIndex { name } => {
+                write!(f, "Array Without Index Error\n    {} is an array, so it requires an index\n    Fix this error by using one of its elements, for example: {}[0].", name, name)
+            }
+            CodeGenErrorKind::InvalidArraySize { name, size_ref } => {
+                write!(f, "Invalid Array Size Error\n    The array {} is declared with the size {}\n    Fix this error by declaring the array with a positive int size.", name, size_ref)
+            }
+            CodeGenErrorKind::InvalidIndexType {
+                array,
+                index_ref,
+                index_type,
+            } => {
+                write!(f, "Provided Incorrect type for Index Error\n    The array {} is indexed with {} which has type {}\n    Fix this error by casting {} to int using static_cast<int>.",
+                    array, index_ref, index_type, index_ref)
+            }
+            CodeGenErrorKind::IndexOutOfRange { array, index, size } => {
+                write!(f, "Index Out of Range Error\n    The array {} is indexed with {}, but its size is {}\n    Fix this error by using an index between 0 and {}.", array, index, size, size - 1)
+            }
+            CodeGenErrorKind::UndeclaredFunction { name } => {
+                write!(f, "Undeclared Function Error\n    Call of Undeclared Function: {}\n    Fix this error by declaring the function before the code that calls it.", name)
+            }
+            CodeGenErrorKind::DuplicateFunction { name } => {
+                write!(f, "Duplicate Function Error\n    The function {} is declared more than once\n    Fix this error by renaming one of the functions.", name)
+            }
+            CodeGenErrorKind::WrongArgumentCount {
+                function,
+                expected,
+                found,
+            } => {
+                write!(f, "Wrong Number of Arguments Error\n    The function {} takes {} arguments, but {} were given\n    Fix this error by passing {} arguments.", function, expected, found, expected)
+            }
+            CodeGenErrorKind::TypeMismatchInArgument {
+                function,
+                expected_type,
+                found_ref,
+                found_type,
+            } => {
+                write!(f, "Provided Incorrect type in Argument Error\n    The function {} expected an argument of type {}\n    But found {} with type {}\n    Fix this error by casting {} to {} using static_cast<{}>.",
+                    function, expected_type, found_ref, found_type, found_ref, expected_type, expected_type)
+            }
+            CodeGenErrorKind::TypeMismatchInReturn {
+                function,
+                expected_type,
+                found,
+            } => {
+                write!(f, "Provided Incorrect type in Return Error\n    ")?;
+                match expected_type {
+                    Some(expected_type) => write!(
+                        f,
+                        "The function {} returns a value of type {}",
+                        function, expected_type
+                    )?,
+                    None => write!(f, "The procedure {} doesn't return a value", function)?,
+                }
+                match found {
+                    Some((found_ref, found_type)) => {
+                        write!(f, "\n    But found {} with type {}", found_ref, found_type)?
+                    }
+                    None => write!(f, "\n    But no value was returned")?,
+                }
+                write!(
+                    f,
+                    "\n    Fix this error by returning a value of the right type."
+                )
+            }
+            CodeGenErrorKind::ReturnOutsideOfFunction => {
+                write!(f, "Return Outside of Function Error\n    A return statement can only appear inside of a function\n    Fix this error by using exit to stop the program.")
+            }
+            CodeGenErrorKind::ProcedureInExpression { name } => {
+                write!(f, "Procedure in Expression Error\n    The procedure {} doesn't return a value, so it can't be used in an expression\n    Fix this error by declaring a return type for {}.", name, name)
+            }
+            CodeGenErrorKind::MissingReturn { function } => {
+                write!(f, "Missing Return Error\n    The function {} may end without returning a value\n    Fix this error by ending {} with a return statement, or with an if statement that returns in both of its branches.", function, function)
+            }
+            CodeGenErrorKind::NotAConstant { name } => {
+                write!(f, "Not a Constant Expression Error\n    The value given to {} can't be computed at compile time\n    Fix this error by using only literals, constants, arithmetic, relational and logical operators in the value.", name)
+            }
+            CodeGenErrorKind::ConstantOverflow { name } => {
+                write!(f, "Constant Overflow Error\n    The value given to {} overflows, or divides by zero, so it can't be computed at compile time\n    Fix this error by keeping every int in the value between -2147483648 and 2147483647, and by not dividing by zero.", name)
+            }
+            CodeGenErrorKind::AssignmentToConstant { name } => {
+                write!(f, "Assignment to Constant Error\n    {} is a constant, so its value can't be changed\n    Fix this error by declaring {} as a variable.", name, name)
+            }
+            CodeGenErrorKind::DuplicateDeclaration { name } => {
+                write!(f, "Duplicate Declaration Error\n    {} is declared more than once in the same scope\n    Fix this error by renaming one of the declarations.", name)
+            }
+            CodeGenErrorKind::ReservedName { name } => {
+                write!(f, "Reserved Name Error\n    {} contains __ or ends with _, which separates the parts of the names the compiler stores variables as\n    Fix this error by renaming {}.", name, name)
+            }
+            CodeGenErrorKind::UnknownInstruction { name } => {
+                write!(f, "Unknown Instruction Error\n    {} is not a Quad instruction\n    Fix this error by using one of the instructions in the Quad spec.", name)
+            }
+            CodeGenErrorKind::WrongOperandCount {
+                instruction,
+                expected,
+                found,
+            } => {
+                write!(f, "Wrong Number of Operands Error\n    The instruction {} takes {} operands, but {} were given\n    Fix this error by passing {} operands.", instruction, expected, found, expected)
+            }
+            CodeGenErrorKind::InvalidOperand {
+                instruction,
+                operand,
+                expected,
+            } => {
+                write!(f, "Invalid Operand Error\n    The instruction {} expected {}\n    But found {}\n    Fix this error by using the instruction that matches the type of the operand.", instruction, expected, operand)
+            }
+            CodeGenErrorKind::UndefinedAsmLabel { name } => {
+                write!(f, "Undefined Label Error\n    The label {} is used, but it is not defined in the asm block\n    Fix this error by defining {} with {}: in the same asm block.", name, name, name)
+            }
+            CodeGenErrorKind::DuplicateAsmLabel { name } => {
+                write!(f, "Duplicate Label Error\n    The label {} is defined more than once in the asm block\n    Fix this error by renaming one of the labels.", name)
+            }
+            CodeGenErrorKind::NegativeAsmLiteral { literal } => {
+                write!(f, "Negative Literal Error\n    The literal {} is negative, but Quad's interpreter can't read a negative literal\n    Fix this error by declaring a constant with the value, and using the constant instead.", literal)
+            }
         };
     }
 }
 
 impl Display for LexingErrorKind {
-    fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        todo!()
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            LexingErrorKind::UnterminatedComment => {
+                write!(f, "Unterminated Comment Error\n    The comment that starts here doesn't end before the end of the file\n    Fix this error by closing the comment with */, every /* inside of it needs its own */.")
+            }
+            LexingErrorKind::UnmatchedCommentEnd => {
+                write!(f, "Unmatched Comment End Error\n    This */ isn't inside of a comment, so it doesn't end one\n    Fix this error by removing the */, or by starting the comment with /*.")
+            }
+        };
     }
 }
 
@@ -180,11 +712,31 @@ impl Display for CompilationErrorKind {
 }
 
 impl Display for CompilationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\n[Line {}, Column {}]", self.line, self.column)?;
+        if let Some(context) = self.context {
+            write!(f, " In {}", context)?;
+        }
+        write!(f, ":\n{}\n", self.err_kind)
+    }
+}
+
+impl Display for CodeGenWarningKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            CodeGenWarningKind::ShadowedVariable { name } => {
+                write!(f, "Shadowed Variable Warning\n    {} is already declared in an enclosing scope, this declaration hides it until the end of the block\n    Fix this warning by renaming one of the variables.", name)
+            }
+        };
+    }
+}
+
+impl Display for CompilationWarning {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "\n[Line {}, Column {}]:\n{}\n",
-            self.line, self.column, self.err_kind
+            "\n[Line {}, Column {}]:\n  Code Generation Warning: {}\n",
+            self.line, self.column, self.warning_kind
         )
     }
 }
@@ -200,6 +752,7 @@ macro_rules! impl_debug_from_display {
 }
 
 impl_debug_from_display!(CompilationError);
+impl_debug_from_display!(CompilationWarning);
 impl_debug_from_display!(CompilationErrorKind);
 impl_debug_from_display!(LexingErrorKind);
 impl_debug_from_display!(CodeGenErrorKind);
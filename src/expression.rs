@@ -1,5 +1,7 @@
 use crate::{
+    boolexpr::{BoolExpr, RelOp},
     codegen::{CodeGenerator, CodeReference, VarType},
+    error::CodeGenErrorKind,
     lexer::Lexeme,
 };
 
@@ -17,10 +19,16 @@ pub struct Expression {
 /// Expression BinaryOp Expression
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum BinaryOp {
-    Add, // +
-    Sub, // -
-    Mul, // *
-    Div, // /
+    Add,    // +
+    Sub,    // -
+    Mul,    // *
+    Div,    // /
+    Mod,    // %
+    BitAnd, // &
+    BitOr,  // |
+    BitXor, // ^
+    Shl,    // <<
+    Shr,    // >>
 }
 
 impl BinaryOp {
@@ -30,17 +38,82 @@ impl BinaryOp {
             "-" => Self::Sub,
             "*" => Self::Mul,
             "/" => Self::Div,
-            _ => panic!("Internal Error: Parsing token Lexeme as Binary Operation failed. \n Expected: +, -, *, / \n Found: {}", l.0),
+            "%" => Self::Mod,
+            "&" => Self::BitAnd,
+            "|" => Self::BitOr,
+            "^" => Self::BitXor,
+            "<<" => Self::Shl,
+            ">>" => Self::Shr,
+            _ => panic!("Internal Error: Parsing token Lexeme as Binary Operation failed. \n Expected: +, -, *, /, %, &, |, ^, <<, >> \n Found: {}", l.0),
         };
     }
+
+    /// For printing
+    pub fn as_str(&self) -> &'static str {
+        return match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Mod => "%",
+            Self::BitAnd => "&",
+            Self::BitOr => "|",
+            Self::BitXor => "^",
+            Self::Shl => "<<",
+            Self::Shr => ">>",
+        };
+    }
+
+    /// Make sure the operation can be applied to the two expressions, % and the bitwise operators only accept ints (and bools).
+    pub fn check_operand_types(
+        self,
+        expr1: &Expression,
+        expr2: &Expression,
+    ) -> Result<(), CodeGenErrorKind> {
+        if matches!(self, Self::Add | Self::Sub | Self::Mul | Self::Div) {
+            return Ok(());
+        }
+        for expr in [expr1, expr2] {
+            if expr.ty == VarType::Float {
+                return Err(CodeGenErrorKind::invalid_operand_type(
+                    self.as_str(),
+                    expr.code_ref.clone(),
+                    expr.ty,
+                ));
+            }
+        }
+        return Ok(());
+    }
+
+    /// &, |, ^, << and >>, Quad has no instructions for them, so they are lowered to arithmetic.
+    fn is_bitwise(self) -> bool {
+        return matches!(
+            self,
+            Self::BitAnd | Self::BitOr | Self::BitXor | Self::Shl | Self::Shr
+        );
+    }
 }
 
 impl Expression {
     /// Cast this expression as another type
     pub fn cast(cast_type: VarType, expr_to_cast: Expression, codegen: &mut CodeGenerator) -> Self {
+        // A bool is already an int with the value 0 or 1, so there is nothing to generate.
+        if (expr_to_cast.ty, cast_type) == (VarType::Bool, VarType::Int) {
+            return Self {
+                ty: cast_type,
+                ..expr_to_cast
+            };
+        }
+        if codegen.folds_constants() {
+            if let Some(folded) = Self::fold_cast(cast_type, &expr_to_cast.code_ref) {
+                return folded;
+            }
+        }
+        let expr_to_cast = expr_to_cast.materialize(codegen);
         let var_name = codegen.new_tmp_var(cast_type);
         let mut code_generated = expr_to_cast.code_generated;
         code_generated.push_str(&codegen.gen_cast_stmt(
+            expr_to_cast.ty,
             cast_type,
             &var_name,
             &expr_to_cast.code_ref,
@@ -62,6 +135,15 @@ impl Expression {
         };
     }
 
+    /// An expression that references the result of this one, without the code it took to generate it
+    pub fn result(&self) -> Self {
+        return Self {
+            ty: self.ty,
+            code_ref: self.code_ref.clone(),
+            code_generated: String::new(),
+        };
+    }
+
     /// An expression that is just an integer literal
     pub fn int_literal(num: i32) -> Self {
         return Self {
@@ -71,6 +153,15 @@ impl Expression {
         };
     }
 
+    /// An expression that is just a boolean literal (true / false)
+    pub fn bool_literal(value: bool) -> Self {
+        return Self {
+            ty: VarType::Bool,
+            code_ref: CodeReference::IntLiteral(value as i32),
+            code_generated: String::new(),
+        };
+    }
+
     /// An expression that is just an float literal
     pub fn float_literal(num: f32) -> Self {
         return Self {
@@ -80,7 +171,209 @@ impl Expression {
         };
     }
 
+    /// Unary minus
+    /// Negating a literal is folded into a negative literal, anything else is subtracted from zero:
+    /// ISUB t 0 a
+    /// OR
+    /// RSUB t 0.0 a
+    pub fn negate(expr: Expression, codegen: &mut CodeGenerator) -> Self {
+        return match expr.code_ref {
+            // -(-2147483648) doesn't fit in an int, so it's left to the generated code
+            CodeReference::IntLiteral(num) if num != i32::MIN => Expression::int_literal(-num),
+            CodeReference::FloatLiteral(num) => Expression::float_literal(-num),
+            CodeReference::IntLiteral(_) | CodeReference::VarName(_) => {
+                let zero = match expr.ty {
+                    VarType::Float => Expression::float_literal(0.0),
+                    VarType::Int | VarType::Bool => Expression::int_literal(0),
+                };
+                Expression::binary_op(zero, expr, BinaryOp::Sub, codegen)
+            }
+        };
+    }
+
+    /// Quad has no negative literals, so a negative literal is computed into a temporary variable before it's used:
+    /// ISUB t 0 {-literal}
+    /// OR
+    /// RSUB t 0.0 {-literal}
+    /// -2147483648 has no positive counterpart, so it's computed in two steps:
+    /// ISUB t 0 2147483647
+    /// ISUB t t 1
+    /// Any other expression is returned as is.
+    pub fn materialize(self, codegen: &mut CodeGenerator) -> Self {
+        let (zero, positive) = match self.code_ref {
+            CodeReference::IntLiteral(num) if num < 0 => (
+                CodeReference::IntLiteral(0),
+                CodeReference::IntLiteral(num.checked_neg().unwrap_or(i32::MAX)),
+            ),
+            CodeReference::FloatLiteral(num) if num < 0.0 => (
+                CodeReference::FloatLiteral(0.0),
+                CodeReference::FloatLiteral(-num),
+            ),
+            _ => return self,
+        };
+        let tmp_var = codegen.new_tmp_var(self.ty);
+        let mut code_generated = self.code_generated;
+        code_generated.push_str(&codegen.bin_op(
+            self.ty,
+            BinaryOp::Sub,
+            &tmp_var,
+            &zero,
+            &positive,
+        ));
+        if self.code_ref == CodeReference::IntLiteral(i32::MIN) {
+            code_generated.push_str(&codegen.bin_op(
+                self.ty,
+                BinaryOp::Sub,
+                &tmp_var,
+                &tmp_var,
+                &CodeReference::IntLiteral(1),
+            ));
+        }
+        return Expression {
+            ty: self.ty,
+            code_ref: tmp_var,
+            code_generated,
+        };
+    }
+
+    /// Conditional expression, only the chosen expression is evaluated.
+    /// If the expressions have different types, their type is combined, and both are cast to it:
+    /// *condition code*
+    /// JMPZ L1 c
+    /// *a code*
+    /// IASN r a
+    /// JUMP L2
+    /// L1: ("false label")
+    /// *b code*
+    /// IASN r b
+    /// L2: ("end label")
+    pub fn conditional(
+        condition: BoolExpr,
+        expr1: Expression,
+        expr2: Expression,
+        codegen: &mut CodeGenerator,
+    ) -> Self {
+        let ty = match expr1.ty == expr2.ty {
+            true => expr1.ty,
+            false => expr1.ty.combine(expr2.ty),
+        };
+        let convert = |expr: Expression, codegen: &mut CodeGenerator| match expr.ty == ty {
+            true => expr,
+            false => Expression::cast(ty, expr, codegen),
+        };
+        if codegen.folds_constants() {
+            // The condition is known at compile time, so only the chosen expression is compiled
+            match condition.code_ref {
+                CodeReference::IntLiteral(0) => return convert(expr2, codegen),
+                CodeReference::IntLiteral(_) => return convert(expr1, codegen),
+                _ => {}
+            }
+        }
+        let expr1 = convert(expr1, codegen).materialize(codegen);
+        let expr2 = convert(expr2, codegen).materialize(codegen);
+        let code_ref = codegen.new_tmp_var(ty);
+        let false_label = codegen.new_label();
+        let end_label = codegen.new_label();
+        let code_generated = format!(
+            "{}{}{}{}{}{}{}{}{}",
+            condition.code_generated,
+            codegen.gen_jump_if_zero(false_label, &condition.code_ref),
+            expr1.code_generated,
+            codegen.gen_copy(ty, &code_ref, &expr1.code_ref),
+            codegen.gen_jump_to_label(end_label),
+            codegen.gen_label_decleration(false_label),
+            expr2.code_generated,
+            codegen.gen_copy(ty, &code_ref, &expr2.code_ref),
+            codegen.gen_label_decleration(end_label),
+        );
+        return Expression {
+            ty,
+            code_ref,
+            code_generated,
+        };
+    }
+
+    /// Compute a cast of a literal at compile time, like RTOI a float is rounded towards zero.
+    fn fold_cast(cast_type: VarType, code_ref: &CodeReference) -> Option<Self> {
+        return match (code_ref, cast_type) {
+            (CodeReference::IntLiteral(num), VarType::Float) => {
+                Some(Expression::float_literal(*num as f32))
+            }
+            (CodeReference::IntLiteral(num), VarType::Bool) => {
+                Some(Expression::bool_literal(*num != 0))
+            }
+            (CodeReference::FloatLiteral(num), VarType::Int) => {
+                Some(Expression::int_literal(*num as i32))
+            }
+            (CodeReference::FloatLiteral(num), VarType::Bool) => {
+                Some(Expression::bool_literal(*num != 0.0))
+            }
+            _ => None,
+        };
+    }
+
+    /// Compute a binary operation between two literals of the same type at compile time, like IDIV an int division rounds down.
+    /// Returns None if one of the operands isn't a literal, or if the result can't be computed (division by zero, overflow).
+    fn fold(
+        ty: VarType,
+        binop: BinaryOp,
+        code_ref1: &CodeReference,
+        code_ref2: &CodeReference,
+    ) -> Option<Self> {
+        return match (code_ref1, code_ref2) {
+            (CodeReference::IntLiteral(a), CodeReference::IntLiteral(b)) => {
+                let (a, b) = (*a, *b);
+                let floor_div = |a: i32, b: i32| {
+                    let quotient = a.checked_div(b)?;
+                    match a % b != 0 && (a < 0) != (b < 0) {
+                        true => Some(quotient - 1),
+                        false => Some(quotient),
+                    }
+                };
+                let result = match binop {
+                    BinaryOp::Add => a.checked_add(b),
+                    BinaryOp::Sub => a.checked_sub(b),
+                    BinaryOp::Mul => a.checked_mul(b),
+                    BinaryOp::Div => floor_div(a, b),
+                    BinaryOp::Mod => floor_div(a, b)
+                        .and_then(|quotient| quotient.checked_mul(b))
+                        .and_then(|product| a.checked_sub(product)),
+                    BinaryOp::BitAnd => Some(a & b),
+                    BinaryOp::BitOr => Some(a | b),
+                    BinaryOp::BitXor => Some(a ^ b),
+                    // Like the generated code, a negative shift doesn't shift
+                    BinaryOp::Shl => 2i32
+                        .checked_pow(b.max(0) as u32)
+                        .and_then(|power| a.checked_mul(power)),
+                    BinaryOp::Shr => Some(a >> b.clamp(0, 31)),
+                }?;
+                Some(Expression {
+                    ty,
+                    code_ref: CodeReference::IntLiteral(result),
+                    code_generated: String::new(),
+                })
+            }
+            (CodeReference::FloatLiteral(a), CodeReference::FloatLiteral(b)) => {
+                let result = match binop {
+                    BinaryOp::Add => a + b,
+                    BinaryOp::Sub => a - b,
+                    BinaryOp::Mul => a * b,
+                    BinaryOp::Div if *b != 0.0 => a / b,
+                    _ => return None,
+                };
+                Some(Expression::float_literal(result))
+            }
+            _ => None,
+        };
+    }
+
     /// A binary operation between two expressions
+    /// Quad has no modulo instruction, so a % b is lowered to a - (a / b) * b:
+    /// IDIV t0 a b
+    /// IMLT t1 t0 b
+    /// ISUB t2 a t1
+    /// IDIV rounds down (towards negative infinity), so the result has the sign of b, for example:
+    /// 7 % -2 = -1, -7 % 2 = 1
     pub fn binary_op(
         mut expr1: Expression,
         mut expr2: Expression,
@@ -89,14 +382,63 @@ impl Expression {
     ) -> Self {
         let ty = expr1.ty.combine(expr2.ty);
         // Infer the type of the resulting expression, if needed, cast the expression to a different type.
-        match (expr1.ty, expr2.ty) {
-            (VarType::Float, VarType::Int) => {
-                expr2 = Expression::cast(VarType::Float, expr2, codegen);
+        if expr1.ty != ty {
+            expr1 = Expression::cast(ty, expr1, codegen);
+        }
+        if expr2.ty != ty {
+            expr2 = Expression::cast(ty, expr2, codegen);
+        }
+        // The bitwise operations generate long loops, so they are computed at compile time whenever they can be.
+        if codegen.folds_constants() || binop.is_bitwise() {
+            if let Some(folded) = Self::fold(ty, binop, &expr1.code_ref, &expr2.code_ref) {
+                return folded;
             }
-            (VarType::Int, VarType::Float) => {
-                expr1 = Expression::cast(VarType::Float, expr1, codegen);
+            if codegen.folds_constants()
+                && !matches!(expr1.code_ref, CodeReference::VarName(_))
+                && !matches!(expr2.code_ref, CodeReference::VarName(_))
+            {
+                // Both operands are literals, so the operation overflowed or divided by zero
+                codegen.set_fold_overflowed();
             }
-            _ => {}
+        }
+        let expr1 = expr1.materialize(codegen);
+        let expr2 = expr2.materialize(codegen);
+        if binop.is_bitwise() {
+            let result = match binop {
+                BinaryOp::Shl | BinaryOp::Shr => Self::shift(&expr1, &expr2, binop, codegen),
+                _ => Self::bitwise(&expr1, &expr2, binop, codegen),
+            };
+            return Expression {
+                code_generated: format!(
+                    "{}{}{}",
+                    expr1.code_generated, expr2.code_generated, result.code_generated
+                ),
+                ..result
+            };
+        }
+        if binop == BinaryOp::Mod {
+            let operand = |code_ref: &CodeReference| Expression {
+                ty,
+                code_ref: code_ref.clone(),
+                code_generated: String::new(),
+            };
+            let quotient = Expression::binary_op(
+                operand(&expr1.code_ref),
+                operand(&expr2.code_ref),
+                BinaryOp::Div,
+                codegen,
+            );
+            let product =
+                Expression::binary_op(quotient, operand(&expr2.code_ref), BinaryOp::Mul, codegen);
+            let remainder =
+                Expression::binary_op(operand(&expr1.code_ref), product, BinaryOp::Sub, codegen);
+            return Expression {
+                code_generated: format!(
+                    "{}{}{}",
+                    expr1.code_generated, expr2.code_generated, remainder.code_generated
+                ),
+                ..remainder
+            };
         }
         // Inherit the code generated of the other expressions.
         let tmp_var = codegen.new_tmp_var(ty);
@@ -112,4 +454,281 @@ impl Expression {
             code_generated,
         };
     }
+
+    /// Bitwise not, ~a is -a - 1 (in two's complement):
+    /// ISUB t0 0 a
+    /// ISUB t1 t0 1
+    /// The bitwise not of a literal is computed at compile time.
+    pub fn bitwise_not(expr: Expression, codegen: &mut CodeGenerator) -> Self {
+        if let CodeReference::IntLiteral(num) = expr.code_ref {
+            return Expression::int_literal(!num);
+        }
+        let negated = Expression::negate(expr, codegen);
+        return Expression::binary_op(negated, Expression::int_literal(1), BinaryOp::Sub, codegen);
+    }
+
+    /// a << n is a * 2^n, and a >> n is a / 2^n (IDIV rounds down, like an arithmetic shift).
+    /// If n is a literal, 2^n is a literal, otherwise it is computed with a loop:
+    /// IASN p 1
+    /// IASN k n
+    /// L1: ("loop label")
+    /// IGRT t k 0
+    /// JMPZ L2 t
+    /// IMLT p p 2
+    /// ISUB k k 1
+    /// JUMP L1
+    /// L2: ("end label")
+    /// IMLT r a p
+    /// OR
+    /// IDIV r a p
+    fn shift(
+        value: &Expression,
+        amount: &Expression,
+        binop: BinaryOp,
+        codegen: &mut CodeGenerator,
+    ) -> Self {
+        let int_ref = |num| CodeReference::IntLiteral(num);
+        let literal_power = match amount.code_ref {
+            CodeReference::IntLiteral(num) => 2i32.checked_pow(num as u32),
+            _ => None,
+        };
+        let mut code_generated = String::new();
+        let power = match literal_power {
+            Some(power) => int_ref(power),
+            None => {
+                let power = codegen.new_tmp_var(VarType::Int);
+                let counter = codegen.new_tmp_var(VarType::Int);
+                let is_positive = codegen.new_tmp_var(VarType::Int);
+                let loop_label = codegen.new_label();
+                let end_label = codegen.new_label();
+                code_generated.push_str(&codegen.gen_copy(VarType::Int, &power, &int_ref(1)));
+                code_generated.push_str(&codegen.gen_copy(
+                    VarType::Int,
+                    &counter,
+                    &amount.code_ref,
+                ));
+                code_generated.push_str(&codegen.gen_label_decleration(loop_label));
+                code_generated.push_str(&codegen.relop(
+                    VarType::Int,
+                    RelOp::Grt,
+                    &is_positive,
+                    &counter,
+                    &int_ref(0),
+                ));
+                code_generated.push_str(&codegen.gen_jump_if_zero(end_label, &is_positive));
+                code_generated.push_str(&codegen.bin_op(
+                    VarType::Int,
+                    BinaryOp::Mul,
+                    &power,
+                    &power,
+                    &int_ref(2),
+                ));
+                code_generated.push_str(&codegen.bin_op(
+                    VarType::Int,
+                    BinaryOp::Sub,
+                    &counter,
+                    &counter,
+                    &int_ref(1),
+                ));
+                code_generated.push_str(&codegen.gen_jump_to_label(loop_label));
+                code_generated.push_str(&codegen.gen_label_decleration(end_label));
+                power
+            }
+        };
+        let arithmetic_op = match binop {
+            BinaryOp::Shl => BinaryOp::Mul,
+            _ => BinaryOp::Div,
+        };
+        let code_ref = codegen.new_tmp_var(VarType::Int);
+        code_generated.push_str(&codegen.bin_op(
+            VarType::Int,
+            arithmetic_op,
+            &code_ref,
+            &value.code_ref,
+            &power,
+        ));
+        return Expression {
+            ty: VarType::Int,
+            code_ref,
+            code_generated,
+        };
+    }
+
+    /// a & b, a | b and a ^ b are computed bit by bit, from the lowest of the 31 value bits to the sign bit.
+    /// Every bit is extracted by dividing by 2 (IDIV rounds down, so a negative number has the bits of its two's complement):
+    /// IASN x a
+    /// IASN y b
+    /// IASN r 0
+    /// IASN w 1
+    /// IASN i 0
+    /// L1: ("loop label")
+    /// ILSS t i 31
+    /// JMPZ L2 t
+    /// *pop the lowest bit of x into bx, and of y into by*
+    /// *the bit of the result, from bx and by*
+    /// IMLT v bit w
+    /// IADD r r v
+    /// IMLT w w 2
+    /// IADD i i 1
+    /// JUMP L1
+    /// L2: ("end label")
+    /// ISUB bx 0 x
+    /// ISUB by 0 y
+    /// *the sign bit of the result, from bx and by*
+    /// IMLT v bit w
+    /// ISUB r r v
+    fn bitwise(
+        expr1: &Expression,
+        expr2: &Expression,
+        binop: BinaryOp,
+        codegen: &mut CodeGenerator,
+    ) -> Self {
+        let int_ref = |num| CodeReference::IntLiteral(num);
+        let mut new_var = || codegen.new_tmp_var(VarType::Int);
+        let (x, y, result, weight, counter) =
+            (new_var(), new_var(), new_var(), new_var(), new_var());
+        let (bit_x, bit_y, bit, is_in_range, value) =
+            (new_var(), new_var(), new_var(), new_var(), new_var());
+        let loop_label = codegen.new_label();
+        let end_label = codegen.new_label();
+
+        let mut code_generated = String::new();
+        code_generated.push_str(&codegen.gen_copy(VarType::Int, &x, &expr1.code_ref));
+        code_generated.push_str(&codegen.gen_copy(VarType::Int, &y, &expr2.code_ref));
+        code_generated.push_str(&codegen.gen_copy(VarType::Int, &result, &int_ref(0)));
+        code_generated.push_str(&codegen.gen_copy(VarType::Int, &weight, &int_ref(1)));
+        code_generated.push_str(&codegen.gen_copy(VarType::Int, &counter, &int_ref(0)));
+        code_generated.push_str(&codegen.gen_label_decleration(loop_label));
+        code_generated.push_str(&codegen.relop(
+            VarType::Int,
+            RelOp::Less,
+            &is_in_range,
+            &counter,
+            &int_ref(31),
+        ));
+        code_generated.push_str(&codegen.gen_jump_if_zero(end_label, &is_in_range));
+        code_generated.push_str(&Self::pop_bit(&x, &bit_x, codegen));
+        code_generated.push_str(&Self::pop_bit(&y, &bit_y, codegen));
+        code_generated.push_str(&Self::bit_op(binop, &bit, &bit_x, &bit_y, codegen));
+        code_generated.push_str(&codegen.bin_op(
+            VarType::Int,
+            BinaryOp::Mul,
+            &value,
+            &bit,
+            &weight,
+        ));
+        code_generated.push_str(&codegen.bin_op(
+            VarType::Int,
+            BinaryOp::Add,
+            &result,
+            &result,
+            &value,
+        ));
+        code_generated.push_str(&codegen.bin_op(
+            VarType::Int,
+            BinaryOp::Mul,
+            &weight,
+            &weight,
+            &int_ref(2),
+        ));
+        code_generated.push_str(&codegen.bin_op(
+            VarType::Int,
+            BinaryOp::Add,
+            &counter,
+            &counter,
+            &int_ref(1),
+        ));
+        code_generated.push_str(&codegen.gen_jump_to_label(loop_label));
+        code_generated.push_str(&codegen.gen_label_decleration(end_label));
+        // After 31 divisions, only the sign is left: 0 or -1
+        code_generated.push_str(&codegen.bin_op(
+            VarType::Int,
+            BinaryOp::Sub,
+            &bit_x,
+            &int_ref(0),
+            &x,
+        ));
+        code_generated.push_str(&codegen.bin_op(
+            VarType::Int,
+            BinaryOp::Sub,
+            &bit_y,
+            &int_ref(0),
+            &y,
+        ));
+        code_generated.push_str(&Self::bit_op(binop, &bit, &bit_x, &bit_y, codegen));
+        code_generated.push_str(&codegen.bin_op(
+            VarType::Int,
+            BinaryOp::Mul,
+            &value,
+            &bit,
+            &weight,
+        ));
+        code_generated.push_str(&codegen.bin_op(
+            VarType::Int,
+            BinaryOp::Sub,
+            &result,
+            &result,
+            &value,
+        ));
+        return Expression {
+            ty: VarType::Int,
+            code_ref: result,
+            code_generated,
+        };
+    }
+
+    // Move the lowest bit of x into bit, and shift x right by one
+    // This will generate:
+    // IDIV q x 2
+    // IMLT m q 2
+    // ISUB bit x m
+    // IASN x q
+    fn pop_bit(x: &CodeReference, bit: &CodeReference, codegen: &mut CodeGenerator) -> String {
+        let two = CodeReference::IntLiteral(2);
+        let quotient = codegen.new_tmp_var(VarType::Int);
+        let multiple = codegen.new_tmp_var(VarType::Int);
+        return format!(
+            "{}{}{}{}",
+            codegen.bin_op(VarType::Int, BinaryOp::Div, &quotient, x, &two),
+            codegen.bin_op(VarType::Int, BinaryOp::Mul, &multiple, &quotient, &two),
+            codegen.bin_op(VarType::Int, BinaryOp::Sub, bit, x, &multiple),
+            codegen.gen_copy(VarType::Int, x, &quotient),
+        );
+    }
+
+    // Apply a bitwise operation to two bits (0 or 1)
+    // This will generate:
+    // IMLT bit a b (for &)
+    // OR
+    // IADD s a b
+    // IMLT p a b
+    // ISUB bit s p (for |, a + b - a * b)
+    // OR
+    // IADD s a b
+    // IMLT p a b
+    // ISUB d s p
+    // ISUB bit d p (for ^, a + b - 2 * a * b)
+    fn bit_op(
+        binop: BinaryOp,
+        bit: &CodeReference,
+        a: &CodeReference,
+        b: &CodeReference,
+        codegen: &mut CodeGenerator,
+    ) -> String {
+        if binop == BinaryOp::BitAnd {
+            return codegen.bin_op(VarType::Int, BinaryOp::Mul, bit, a, b);
+        }
+        let sum = codegen.new_tmp_var(VarType::Int);
+        let product = codegen.new_tmp_var(VarType::Int);
+        let mut output = codegen.bin_op(VarType::Int, BinaryOp::Add, &sum, a, b);
+        output.push_str(&codegen.bin_op(VarType::Int, BinaryOp::Mul, &product, a, b));
+        if binop == BinaryOp::BitOr {
+            output.push_str(&codegen.bin_op(VarType::Int, BinaryOp::Sub, bit, &sum, &product));
+            return output;
+        }
+        let difference = codegen.new_tmp_var(VarType::Int);
+        output.push_str(&codegen.bin_op(VarType::Int, BinaryOp::Sub, &difference, &sum, &product));
+        output.push_str(&codegen.bin_op(VarType::Int, BinaryOp::Sub, bit, &difference, &product));
+        return output;
+    }
 }
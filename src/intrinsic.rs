@@ -0,0 +1,473 @@
+use crate::{
+    boolexpr::{BoolExpr, RelOp},
+    codegen::{CodeGenerator, CodeReference, VarType},
+    error::CodeGenErrorKind,
+    expression::{BinaryOp, Expression},
+};
+
+/// 2π, a float argument of sin is reduced to (-2π, 2π) before its series is computed.
+/// A float literal is too short for 2π, so it's split into a part whose multiples are exact and the rest of it.
+const TWO_PI_HIGH: f32 = 6.28125;
+const TWO_PI_LOW: f32 = (std::f64::consts::TAU - TWO_PI_HIGH as f64) as f32;
+/// The number of terms of the series of sin, enough for any argument in (-2π, 2π).
+const SIN_TERMS: i32 = 20;
+
+/// A function the compiler provides, it is used when there is no declared function with the same name.
+/// abs, min and max are generated where they are called, pow, sqrt and sin are subroutines that are only generated if they are called.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Intrinsic {
+    Abs,  // abs(x)
+    Min,  // min(a, b)
+    Max,  // max(a, b)
+    Pow,  // pow(base, exponent), the exponent is an int
+    Sqrt, // sqrt(x), 0 if x isn't positive
+    Sin,  // sin(x), x is in radians
+}
+
+impl Intrinsic {
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name {
+            "abs" => Some(Self::Abs),
+            "min" => Some(Self::Min),
+            "max" => Some(Self::Max),
+            "pow" => Some(Self::Pow),
+            "sqrt" => Some(Self::Sqrt),
+            "sin" => Some(Self::Sin),
+            _ => None,
+        };
+    }
+
+    /// For printing
+    pub fn as_str(&self) -> &'static str {
+        return match self {
+            Self::Abs => "abs",
+            Self::Min => "min",
+            Self::Max => "max",
+            Self::Pow => "pow",
+            Self::Sqrt => "sqrt",
+            Self::Sin => "sin",
+        };
+    }
+
+    fn arg_count(&self) -> usize {
+        return match self {
+            Self::Abs | Self::Sqrt | Self::Sin => 1,
+            Self::Min | Self::Max | Self::Pow => 2,
+        };
+    }
+
+    /// Call the intrinsic, the type of abs / min / max is the combined type of their arguments,
+    /// the type of pow is the type of its base, and sqrt / sin always return a float.
+    pub fn gen_call(
+        self,
+        args: Vec<Expression>,
+        line: usize,
+        codegen: &mut CodeGenerator,
+    ) -> Result<Expression, CodeGenErrorKind> {
+        if args.len() != self.arg_count() {
+            return Err(CodeGenErrorKind::wrong_argument_count(
+                self.as_str(),
+                self.arg_count(),
+                args.len(),
+            ));
+        }
+        let mut args = args.into_iter();
+        let mut arg = || args.next().unwrap();
+        return match self {
+            Self::Abs => Ok(Self::gen_abs(arg(), codegen)),
+            Self::Min => Ok(Self::gen_select(arg(), arg(), RelOp::Less, codegen)),
+            Self::Max => Ok(Self::gen_select(arg(), arg(), RelOp::Grt, codegen)),
+            Self::Pow => Self::gen_pow(arg(), arg(), line, codegen),
+            Self::Sqrt => codegen.gen_subroutine_call(
+                "_sqrt",
+                &[("_sqrt_x", VarType::Float)],
+                VarType::Float,
+                Self::gen_sqrt_body,
+                vec![arg()],
+                line,
+            ),
+            Self::Sin => codegen.gen_subroutine_call(
+                "_sin",
+                &[("_sin_x", VarType::Float)],
+                VarType::Float,
+                Self::gen_sin_body,
+                vec![arg()],
+                line,
+            ),
+        };
+    }
+
+    // abs(x) is x < 0 ? -x : x
+    fn gen_abs(x: Expression, codegen: &mut CodeGenerator) -> Expression {
+        let x = Self::as_number(x, codegen).materialize(codegen);
+        let zero = match x.ty {
+            VarType::Float => Expression::float_literal(0.0),
+            VarType::Int | VarType::Bool => Expression::int_literal(0),
+        };
+        let is_negative = BoolExpr::relop(x.result(), zero, RelOp::Less, codegen);
+        let negated = Expression::negate(x.result(), codegen);
+        let abs = Expression::conditional(is_negative, negated, x.result(), codegen);
+        return Expression {
+            code_generated: x.code_generated + &abs.code_generated,
+            ..abs
+        };
+    }
+
+    // min(a, b) is a < b ? a : b, and max(a, b) is a > b ? a : b
+    fn gen_select(
+        a: Expression,
+        b: Expression,
+        relop: RelOp,
+        codegen: &mut CodeGenerator,
+    ) -> Expression {
+        let ty = a.ty.combine(b.ty);
+        let a = Self::convert(ty, a, codegen);
+        let b = Self::convert(ty, b, codegen);
+        let is_a = BoolExpr::relop(a.result(), b.result(), relop, codegen);
+        let selected = Expression::conditional(is_a, a.result(), b.result(), codegen);
+        return Expression {
+            code_generated: a.code_generated + &b.code_generated + &selected.code_generated,
+            ..selected
+        };
+    }
+
+    // pow(base, exponent) is a subroutine for every type of base
+    fn gen_pow(
+        base: Expression,
+        exponent: Expression,
+        line: usize,
+        codegen: &mut CodeGenerator,
+    ) -> Result<Expression, CodeGenErrorKind> {
+        if exponent.ty == VarType::Float {
+            return Err(CodeGenErrorKind::argument_type_mismatch(
+                Self::Pow.as_str(),
+                VarType::Int,
+                exponent.code_ref,
+                exponent.ty,
+            ));
+        }
+        let exponent = Self::as_number(exponent, codegen);
+        let base = Self::as_number(base, codegen);
+        let (name, params): (_, &[_]) = match base.ty {
+            VarType::Float => (
+                "_rpow",
+                &[
+                    ("_rpow_base", VarType::Float),
+                    ("_rpow_exponent", VarType::Int),
+                ],
+            ),
+            VarType::Int | VarType::Bool => (
+                "_ipow",
+                &[
+                    ("_ipow_base", VarType::Int),
+                    ("_ipow_exponent", VarType::Int),
+                ],
+            ),
+        };
+        let ty = base.ty;
+        return codegen.gen_subroutine_call(
+            name,
+            params,
+            ty,
+            |codegen, result| Self::gen_pow_body(codegen, ty, params, result),
+            vec![base, exponent],
+            line,
+        );
+    }
+
+    // A bool argument is used as an int
+    fn as_number(expr: Expression, codegen: &mut CodeGenerator) -> Expression {
+        return Self::convert(expr.ty.combine(expr.ty), expr, codegen);
+    }
+
+    fn convert(ty: VarType, expr: Expression, codegen: &mut CodeGenerator) -> Expression {
+        let expr = match expr.ty == ty {
+            true => expr,
+            false => Expression::cast(ty, expr, codegen),
+        };
+        return expr.materialize(codegen);
+    }
+
+    // Multiply the result by the base |exponent| times, a negative exponent divides 1 by the result (like IDIV for an int base).
+    // This will generate:
+    // XASN r 1
+    // IASN k e
+    // ILSS n e 0
+    // JMPZ L1 n
+    // ISUB k 0 e
+    // L1: ("loop label")
+    // IGRT t k 0
+    // JMPZ L2 t
+    // XMLT r r b
+    // ISUB k k 1
+    // JUMP L1
+    // L2: ("end label")
+    // JMPZ L3 n
+    // XDIV r 1 r
+    // L3: ("positive label")
+    fn gen_pow_body(
+        codegen: &mut CodeGenerator,
+        ty: VarType,
+        params: &[(&str, VarType)],
+        result: &CodeReference,
+    ) -> String {
+        let base = CodeReference::VarName(Box::from(params[0].0));
+        let exponent = CodeReference::VarName(Box::from(params[1].0));
+        let one = match ty {
+            VarType::Float => CodeReference::FloatLiteral(1.0),
+            VarType::Int | VarType::Bool => CodeReference::IntLiteral(1),
+        };
+        let counter = codegen.new_tmp_var(VarType::Int);
+        let is_negative = codegen.new_tmp_var(VarType::Int);
+        let is_positive_counter = codegen.new_tmp_var(VarType::Int);
+        let loop_label = codegen.new_label();
+        let end_label = codegen.new_label();
+        let positive_label = codegen.new_label();
+        let zero = CodeReference::IntLiteral(0);
+        let int_one = CodeReference::IntLiteral(1);
+
+        let mut output = codegen.gen_copy(ty, result, &one);
+        output.push_str(&codegen.gen_copy(VarType::Int, &counter, &exponent));
+        output.push_str(&codegen.relop(VarType::Int, RelOp::Less, &is_negative, &exponent, &zero));
+        output.push_str(&codegen.gen_jump_if_zero(loop_label, &is_negative));
+        output.push_str(&codegen.bin_op(VarType::Int, BinaryOp::Sub, &counter, &zero, &exponent));
+        output.push_str(&codegen.gen_label_decleration(loop_label));
+        output.push_str(&codegen.relop(
+            VarType::Int,
+            RelOp::Grt,
+            &is_positive_counter,
+            &counter,
+            &zero,
+        ));
+        output.push_str(&codegen.gen_jump_if_zero(end_label, &is_positive_counter));
+        output.push_str(&codegen.bin_op(ty, BinaryOp::Mul, result, result, &base));
+        output.push_str(&codegen.bin_op(VarType::Int, BinaryOp::Sub, &counter, &counter, &int_one));
+        output.push_str(&codegen.gen_jump_to_label(loop_label));
+        output.push_str(&codegen.gen_label_decleration(end_label));
+        output.push_str(&codegen.gen_jump_if_zero(positive_label, &is_negative));
+        output.push_str(&codegen.bin_op(ty, BinaryOp::Div, result, &one, result));
+        output.push_str(&codegen.gen_label_decleration(positive_label));
+        return output;
+    }
+
+    // Newton's method, starting from x + 1 (which is larger than the root), stops when the next guess isn't smaller.
+    // This will generate:
+    // RGRT p x 0.0
+    // JMPZ L3 p
+    // RADD r x 1.0
+    // L1: ("loop label")
+    // RDIV q x r
+    // RADD s r q
+    // RDIV g s 2.0
+    // RLSS t g r
+    // JMPZ L2 t
+    // RASN r g
+    // JUMP L1
+    // L3: ("not positive label")
+    // RASN r 0.0
+    // L2: ("end label")
+    fn gen_sqrt_body(codegen: &mut CodeGenerator, result: &CodeReference) -> String {
+        let x = CodeReference::VarName(Box::from("_sqrt_x"));
+        let is_positive = codegen.new_tmp_var(VarType::Int);
+        let quotient = codegen.new_tmp_var(VarType::Float);
+        let sum = codegen.new_tmp_var(VarType::Float);
+        let guess = codegen.new_tmp_var(VarType::Float);
+        let is_smaller = codegen.new_tmp_var(VarType::Int);
+        let loop_label = codegen.new_label();
+        let end_label = codegen.new_label();
+        let not_positive_label = codegen.new_label();
+        let zero = CodeReference::FloatLiteral(0.0);
+
+        let mut output = codegen.relop(VarType::Float, RelOp::Grt, &is_positive, &x, &zero);
+        output.push_str(&codegen.gen_jump_if_zero(not_positive_label, &is_positive));
+        output.push_str(&codegen.bin_op(
+            VarType::Float,
+            BinaryOp::Add,
+            result,
+            &x,
+            &CodeReference::FloatLiteral(1.0),
+        ));
+        output.push_str(&codegen.gen_label_decleration(loop_label));
+        output.push_str(&codegen.bin_op(VarType::Float, BinaryOp::Div, &quotient, &x, result));
+        output.push_str(&codegen.bin_op(VarType::Float, BinaryOp::Add, &sum, result, &quotient));
+        output.push_str(&codegen.bin_op(
+            VarType::Float,
+            BinaryOp::Div,
+            &guess,
+            &sum,
+            &CodeReference::FloatLiteral(2.0),
+        ));
+        output.push_str(&codegen.relop(VarType::Float, RelOp::Less, &is_smaller, &guess, result));
+        output.push_str(&codegen.gen_jump_if_zero(end_label, &is_smaller));
+        output.push_str(&codegen.gen_copy(VarType::Float, result, &guess));
+        output.push_str(&codegen.gen_jump_to_label(loop_label));
+        output.push_str(&codegen.gen_label_decleration(not_positive_label));
+        output.push_str(&codegen.gen_copy(VarType::Float, result, &zero));
+        output.push_str(&codegen.gen_label_decleration(end_label));
+        return output;
+    }
+
+    // Reduce x to y in (-2π, 2π), then sum the series y - y^3/3! + y^5/5! - ...
+    // The whole turns are subtracted in two parts so the rounding of 2π doesn't grow with them.
+    // Every term is the previous one times -y^2 / ((2n)(2n + 1)).
+    // This will generate:
+    // RDIV q x 6.2831855
+    // RTOI k q
+    // ITOR kf k
+    // RMLT m kf 6.28125
+    // RSUB h x m
+    // RMLT l kf 0.0019353072
+    // RSUB y h l
+    // RASN term y
+    // RASN r y
+    // RMLT y2 y y
+    // IASN n 1
+    // L1: ("loop label")
+    // ILSS t n 21
+    // JMPZ L2 t
+    // IMLT a n 2
+    // IADD b a 1
+    // IMLT c a b
+    // ITOR cf c
+    // RMLT u term y2
+    // RDIV v u cf
+    // RSUB term 0.0 v
+    // RADD r r term
+    // IADD n n 1
+    // JUMP L1
+    // L2: ("end label")
+    fn gen_sin_body(codegen: &mut CodeGenerator, result: &CodeReference) -> String {
+        let x = CodeReference::VarName(Box::from("_sin_x"));
+        let two_pi_high = CodeReference::FloatLiteral(TWO_PI_HIGH);
+        let two_pi_low = CodeReference::FloatLiteral(TWO_PI_LOW);
+        let turns = codegen.new_tmp_var(VarType::Float);
+        let whole_turns = codegen.new_tmp_var(VarType::Int);
+        let whole_turns_float = codegen.new_tmp_var(VarType::Float);
+        let reduction_high = codegen.new_tmp_var(VarType::Float);
+        let reduced_high = codegen.new_tmp_var(VarType::Float);
+        let reduction_low = codegen.new_tmp_var(VarType::Float);
+        let y = codegen.new_tmp_var(VarType::Float);
+        let term = codegen.new_tmp_var(VarType::Float);
+        let y_squared = codegen.new_tmp_var(VarType::Float);
+        let n = codegen.new_tmp_var(VarType::Int);
+        let in_series = codegen.new_tmp_var(VarType::Int);
+        let double_n = codegen.new_tmp_var(VarType::Int);
+        let double_n_plus_one = codegen.new_tmp_var(VarType::Int);
+        let divisor = codegen.new_tmp_var(VarType::Int);
+        let divisor_float = codegen.new_tmp_var(VarType::Float);
+        let product = codegen.new_tmp_var(VarType::Float);
+        let quotient = codegen.new_tmp_var(VarType::Float);
+        let loop_label = codegen.new_label();
+        let end_label = codegen.new_label();
+        let int_one = CodeReference::IntLiteral(1);
+
+        let mut output = codegen.bin_op(
+            VarType::Float,
+            BinaryOp::Div,
+            &turns,
+            &x,
+            &CodeReference::FloatLiteral(std::f32::consts::TAU),
+        );
+        output.push_str(&codegen.gen_cast_stmt(VarType::Float, VarType::Int, &whole_turns, &turns));
+        output.push_str(&codegen.gen_cast_stmt(
+            VarType::Int,
+            VarType::Float,
+            &whole_turns_float,
+            &whole_turns,
+        ));
+        output.push_str(&codegen.bin_op(
+            VarType::Float,
+            BinaryOp::Mul,
+            &reduction_high,
+            &whole_turns_float,
+            &two_pi_high,
+        ));
+        output.push_str(&codegen.bin_op(
+            VarType::Float,
+            BinaryOp::Sub,
+            &reduced_high,
+            &x,
+            &reduction_high,
+        ));
+        output.push_str(&codegen.bin_op(
+            VarType::Float,
+            BinaryOp::Mul,
+            &reduction_low,
+            &whole_turns_float,
+            &two_pi_low,
+        ));
+        output.push_str(&codegen.bin_op(
+            VarType::Float,
+            BinaryOp::Sub,
+            &y,
+            &reduced_high,
+            &reduction_low,
+        ));
+        output.push_str(&codegen.gen_copy(VarType::Float, &term, &y));
+        output.push_str(&codegen.gen_copy(VarType::Float, result, &y));
+        output.push_str(&codegen.bin_op(VarType::Float, BinaryOp::Mul, &y_squared, &y, &y));
+        output.push_str(&codegen.gen_copy(VarType::Int, &n, &int_one));
+        output.push_str(&codegen.gen_label_decleration(loop_label));
+        output.push_str(&codegen.relop(
+            VarType::Int,
+            RelOp::Less,
+            &in_series,
+            &n,
+            &CodeReference::IntLiteral(SIN_TERMS + 1),
+        ));
+        output.push_str(&codegen.gen_jump_if_zero(end_label, &in_series));
+        output.push_str(&codegen.bin_op(
+            VarType::Int,
+            BinaryOp::Mul,
+            &double_n,
+            &n,
+            &CodeReference::IntLiteral(2),
+        ));
+        output.push_str(&codegen.bin_op(
+            VarType::Int,
+            BinaryOp::Add,
+            &double_n_plus_one,
+            &double_n,
+            &int_one,
+        ));
+        output.push_str(&codegen.bin_op(
+            VarType::Int,
+            BinaryOp::Mul,
+            &divisor,
+            &double_n,
+            &double_n_plus_one,
+        ));
+        output.push_str(&codegen.gen_cast_stmt(
+            VarType::Int,
+            VarType::Float,
+            &divisor_float,
+            &divisor,
+        ));
+        output.push_str(&codegen.bin_op(
+            VarType::Float,
+            BinaryOp::Mul,
+            &product,
+            &term,
+            &y_squared,
+        ));
+        output.push_str(&codegen.bin_op(
+            VarType::Float,
+            BinaryOp::Div,
+            &quotient,
+            &product,
+            &divisor_float,
+        ));
+        output.push_str(&codegen.bin_op(
+            VarType::Float,
+            BinaryOp::Sub,
+            &term,
+            &CodeReference::FloatLiteral(0.0),
+            &quotient,
+        ));
+        output.push_str(&codegen.bin_op(VarType::Float, BinaryOp::Add, result, result, &term));
+        output.push_str(&codegen.bin_op(VarType::Int, BinaryOp::Add, &n, &n, &int_one));
+        output.push_str(&codegen.gen_jump_to_label(loop_label));
+        output.push_str(&codegen.gen_label_decleration(end_label));
+        return output;
+    }
+}
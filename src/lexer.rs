@@ -1,4 +1,4 @@
-use crate::token::*;
+use crate::{error::*, token::*};
 
 #[repr(transparent)]
 pub struct Lexeme(pub Box<str>);
@@ -22,13 +22,21 @@ pub struct Lexer {
     lines: Vec<String>,
     current_line: usize,
     current_char: usize,
-    in_comment: bool,
+    // The line and column of every block comment that wasn't closed yet, comments can be nested.
+    open_comments: Vec<(usize, usize)>,
     regex_set: regex::RegexSet,
+    errors: Vec<CompilationError>,
 }
 
 impl Lexer {
-    pub fn lex_tokens(source_code: String) -> Vec<LexedToken> {
-        return Lexer::new(source_code).get_all_tokens();
+    /// Lex all of the tokens, and return them with the errors found while lexing.
+    pub fn lex(source_code: String) -> (Vec<LexedToken>, Vec<CompilationError>) {
+        let mut lexer = Lexer::new(source_code);
+        let mut toks = Vec::new();
+        while let Some(tok) = lexer.get_next_token() {
+            toks.push(tok);
+        }
+        return (toks, lexer.errors);
     }
 
     pub fn new(source_code: String) -> Lexer {
@@ -36,8 +44,9 @@ impl Lexer {
             lines: source_code.lines().map(|slc| String::from(slc)).collect(),
             current_line: 0,
             current_char: 0,
-            in_comment: false,
+            open_comments: Vec::new(),
             regex_set: build_regex_set(),
+            errors: Vec::new(),
         };
     }
 
@@ -49,6 +58,15 @@ impl Lexer {
     // The main function of the Lexer
     pub fn get_next_token(&mut self) -> Option<LexedToken> {
         if self.current_line >= self.lines.len() {
+            // The outermost comment that wasn't closed swallowed the rest of the file
+            if let Some(&(line, column)) = self.open_comments.first() {
+                self.errors.push(CompilationError::lexing_error(
+                    line,
+                    column,
+                    LexingErrorKind::UnterminatedComment,
+                ));
+                self.open_comments.clear();
+            }
             return None;
         }
         if self.current_char >= self.lines[self.current_line].len() {
@@ -67,22 +85,37 @@ impl Lexer {
         let match_index = regex_matches.iter().next().unwrap();
         let matched = &REGEX_TABLE[match_index].0;
 
+        let in_comment = !self.open_comments.is_empty();
+        let start_column = self.current_char + 1;
         self.current_char += line.len();
         match matched {
             RegexMatch::NonToken(non_token) => match non_token {
                 NonToken::StartComment => {
-                    self.in_comment = true;
+                    self.open_comments
+                        .push((self.current_line + 1, start_column));
+                }
+                NonToken::EndComment if in_comment => {
+                    self.open_comments.pop();
                 }
+                // A */ that doesn't close a comment would otherwise be silently ignored
                 NonToken::EndComment => {
-                    self.in_comment = false;
+                    self.errors.push(CompilationError::lexing_error(
+                        self.current_line + 1,
+                        start_column,
+                        LexingErrorKind::UnmatchedCommentEnd,
+                    ));
+                }
+                // A line comment inside of a block comment is ignored, like the rest of the block comment
+                NonToken::LineComment if !in_comment => {
+                    self.new_line();
                 }
-                NonToken::Error(err) if !self.in_comment => {
+                NonToken::Error(err) if !in_comment => {
                     eprintln!("Lexing Error at line {}: {}", self.current_line, err);
                     self.new_line();
                 }
                 _ => {}
             },
-            RegexMatch::Token(token) if !self.in_comment => {
+            RegexMatch::Token(token) if !in_comment => {
                 return Some(LexedToken {
                     lexeme: Lexeme(line.into()),
                     token: *token,
@@ -96,13 +129,4 @@ impl Lexer {
         // If we didn't return a token, then we need to get the next one.
         return self.get_next_token();
     }
-
-    // Lex all the tokens at once
-    pub fn get_all_tokens(mut self) -> Vec<LexedToken> {
-        let mut toks = Vec::new();
-        while let Some(tok) = self.get_next_token() {
-            toks.push(tok);
-        }
-        return toks;
-    }
 }
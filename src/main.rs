@@ -1,12 +1,15 @@
+mod asm;
 mod boolexpr;
 mod codegen;
 mod compiler;
 pub mod error;
 mod expression;
+mod intrinsic;
 mod lexer;
 mod parser;
 mod token;
 
+use crate::codegen::DEFAULT_MAX_CALL_DEPTH;
 use crate::compiler::Compiler;
 use std::fs::{read_to_string, write, File};
 use std::path::Path;
@@ -16,12 +19,25 @@ const INPUT_DIRECTORY: &str = "input";
 const OUTPUT_DIRECTORY: &str = "output";
 const INPUT_FILE_EXTENSION: &str = "ou";
 const OUTPUT_FILE_EXTENSION: &str = "qud";
+const MAX_CALL_DEPTH_FLAG: &str = "--max-call-depth=";
 
 fn main() -> Result<(), &'static str> {
     let input_dir = Path::new(INPUT_DIRECTORY);
     let output_dir = Path::new(OUTPUT_DIRECTORY);
 
-    let args: Vec<String> = std::env::args().collect();
+    let mut args: Vec<String> = std::env::args().collect();
+
+    // The flag can appear anywhere after the program name
+    let mut max_call_depth = DEFAULT_MAX_CALL_DEPTH;
+    if let Some(flag_index) = args
+        .iter()
+        .position(|arg| arg.starts_with(MAX_CALL_DEPTH_FLAG))
+    {
+        let flag = args.remove(flag_index);
+        max_call_depth = flag[MAX_CALL_DEPTH_FLAG.len()..]
+            .parse()
+            .map_err(|_| "The maximum call depth must be a non-negative integer")?;
+    }
 
     if args.len() == 1 {
         // Iterate over all of the files in the input folder
@@ -47,7 +63,7 @@ fn main() -> Result<(), &'static str> {
                     .with_extension(OUTPUT_FILE_EXTENSION),
             );
 
-            if let Some(compiled) = compile_file(input_file_path) {
+            if let Some(compiled) = compile_file(input_file_path, max_call_depth) {
                 File::create(&output_file_path)
                     .expect("Couldn't create the output file")
                     .set_len(0)
@@ -65,7 +81,7 @@ fn main() -> Result<(), &'static str> {
         for file in files {
             let input_file_path = Path::new(&file);
             let output_file_path = input_file_path.with_extension(OUTPUT_FILE_EXTENSION);
-            if let Some(compiled) = compile_file(input_file_path) {
+            if let Some(compiled) = compile_file(input_file_path, max_call_depth) {
                 File::create(&output_file_path)
                     .expect("Couldn't create the output file")
                     .set_len(0)
@@ -79,7 +95,7 @@ fn main() -> Result<(), &'static str> {
     Ok(())
 }
 
-fn compile_file(input_file_path: &Path) -> Option<String> {
+fn compile_file(input_file_path: &Path, max_call_depth: usize) -> Option<String> {
     let file_extension = input_file_path.extension();
 
     if file_extension.is_none() {
@@ -98,6 +114,7 @@ fn compile_file(input_file_path: &Path) -> Option<String> {
         println!("\n~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
         println!("         Compiling {:?}", input_file_path);
         return Compiler::init(input_as_string.clone())
+            .with_max_call_depth(max_call_depth)
             .compile()
             .inspect(|_| {
                 println!("\n         Compiled {:?} Successfully", input_file_path);
@@ -112,18 +129,18 @@ fn compile_file(input_file_path: &Path) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::parser::Parser;
-    use crate::{codegen::VarType, lexer::Lexer};
+    use crate::{codegen::VarType, lexer::Lexer, token::*};
 
     #[test]
     fn test_expressions() {
         compilation_test_template(
             "2 + 2 * 3 + 1",
-            "IMLT _t0 2 3\nIADD _t1 _t0 1\nIADD _t2 2 _t1\n",
+            "IMLT _t0 2 3\nIADD _t1 2 _t0\nIADD _t2 _t1 1\n",
             compile_expression,
         );
         compilation_test_template(
             "2.0 + 2 * 3 + 1",
-            "IMLT _t0 2 3\nIADD _t1 _t0 1\nITOR _t2 _t1\nRADD _t3 2.0 _t2\n",
+            "IMLT _t0 2 3\nITOR _t1 _t0\nRADD _t2 2.0 _t1\nITOR _t3 1\nRADD _t4 _t2 _t3\n",
             compile_expression,
         );
         compilation_test_template(
@@ -131,6 +148,11 @@ mod tests {
             "ITOR _t0 2\nRADD _t1 2.0 _t0\nITOR _t2 3\nRMLT _t3 _t1 _t2\nRADD _t4 _t3 1.0\n",
             compile_expression,
         );
+        compilation_test_template(
+            "8 - 4 - 2 / 2 / 2",
+            "ISUB _t0 8 4\nIDIV _t1 2 2\nIDIV _t2 _t1 2\nISUB _t3 _t0 _t2\n",
+            compile_expression,
+        );
         assert_eq!(
             "ITOR _t0 var2\nRADD _t1 var _t0\nRMLT _t2 var _t1\n",
             compile_expression_with_variables(
@@ -143,20 +165,108 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_unary_minus() {
+        compilation_test_template(
+            "-2 * -(3) + +1",
+            "ISUB _t0 0 2\nISUB _t1 0 3\nIMLT _t2 _t0 _t1\nIADD _t3 _t2 1\n",
+            compile_expression,
+        );
+        assert_eq!(
+            "RSUB _t0 0.0 var\nRSUB _t1 0.0 1.5\nRMLT _t2 _t0 _t1\n",
+            compile_expression_with_variables(
+                "-var * -1.5",
+                &[(String::from("var"), VarType::Float)]
+            )
+        );
+    }
+
+    #[test]
+    fn test_modulo() {
+        compilation_test_template(
+            "7 % -2",
+            "ISUB _t0 0 2\nIDIV _t1 7 _t0\nIMLT _t2 _t1 _t0\nISUB _t3 7 _t2\n",
+            compile_expression,
+        );
+        compilation_test_template(
+            "7 % 4 * 2",
+            "IDIV _t0 7 4\nIMLT _t1 _t0 4\nISUB _t2 7 _t1\nIMLT _t3 _t2 2\n",
+            compile_expression,
+        );
+    }
+
+    #[should_panic(expected = "Incorrect type for Operator")]
+    #[test]
+    fn test_error_modulo_float() {
+        compile_program("f: float; { output(f % 2); }");
+    }
+
     #[test]
     fn test_bool_expression() {
         compilation_test_template(
             "1 + 1 > 3 || 2 == 1 + 1",
             "IADD _t0 1 1\n\
             IGRT _t1 _t0 3\n\
-            ISUB _t4 1 _t1\n\
+            IASN _t4 _t1\n\
+            JMPZ L0 _t4\n\
+            JUMP L1\n\
+            L0:\n\
             IADD _t2 1 1\n\
             IEQL _t3 2 _t2\n\
-            ISUB _t5 1 _t3\n\
-            IMLT _t6 _t4 _t5\n\
-            ISUB _t7 1 _t6\n",
+            IASN _t4 _t3\n\
+            L1:\n",
             compile_bool_expression,
         );
+        assert_eq!(
+            "IGRT _t0 b 0\n\
+            IASN _t3 _t0\n\
+            JMPZ L0 _t3\n\
+            IDIV _t1 a b\n\
+            IGRT _t2 _t1 1\n\
+            IASN _t3 _t2\n\
+            L0:\n",
+            compile_bool_expression_with_variables(
+                "b > 0 && a / b > 1",
+                &[
+                    (String::from("a"), VarType::Int),
+                    (String::from("b"), VarType::Int)
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_conditions() {
+        // Every operand of && and || jumps to where the statement continues
+        compilation_test_template(
+            "a, b: int; { if (a > 0 && b > 0 || a < b) a = 1; do a = a - 1; while (a > 0 && b > 0 || a < b); }",
+            "IGRT _t0 a 0\n\
+            JMPZ L3 _t0\n\
+            IGRT _t1 b 0\n\
+            JMPZ L3 _t1\n\
+            JUMP L2\n\
+            L3:\n\
+            ILSS _t2 a b\n\
+            JMPZ L0 _t2\n\
+            L2:\n\
+            IASN a 1\n\
+            L0:\n\
+            L4:\n\
+            ISUB _t3 a 1\n\
+            IASN a _t3\n\
+            L5:\n\
+            IGRT _t4 a 0\n\
+            JMPZ L7 _t4\n\
+            ILSS _t5 b 1\n\
+            JMPZ L4 _t5\n\
+            L7:\n\
+            ILSS _t6 a b\n\
+            ISUB _t7 1 _t6\n\
+            JMPZ L4 _t7\n\
+            L6:\n\
+            HALT",
+            compile_program,
+        );
     }
 
     #[should_panic(expected = "Undeclared Variable")]
@@ -177,6 +287,873 @@ mod tests {
         compile_bool_expression("1 + 1");
     }
 
+    #[test]
+    fn test_switch() {
+        compilation_test_template(
+            "a: int; { switch (a) { case 1: output(1); case 2: output(2); default: output(0); } }",
+            "JUMP L0\n\
+            L2:\n\
+            IPRT 1\n\
+            L3:\n\
+            IPRT 2\n\
+            L4:\n\
+            IPRT 0\n\
+            JUMP L1\n\
+            L0:\n\
+            INQL _t0 a 1\n\
+            JMPZ L2 _t0\n\
+            INQL _t1 a 2\n\
+            JMPZ L3 _t1\n\
+            JUMP L4\n\
+            L1:\n\
+            HALT",
+            compile_program,
+        );
+    }
+
+    #[should_panic(expected = "Duplicate Case")]
+    #[test]
+    fn test_error_duplicate_case() {
+        compile_program("a: int; { switch (a) { case 1: case 1: default: } }");
+    }
+
+    #[should_panic(expected = "Incorrect type in Case")]
+    #[test]
+    fn test_error_case_type() {
+        compile_program("a: int; { switch (a) { case 1.5: default: } }");
+    }
+
+    #[test]
+    fn test_break() {
+        compilation_test_template(
+            "a: int; { while (a < 10) { switch (a) { case 1: break; default: break; } break; } }",
+            "L0:\n\
+            ILSS _t0 a 10\n\
+            JMPZ L1 _t0\n\
+            JUMP L2\n\
+            L4:\n\
+            JUMP L3\n\
+            L5:\n\
+            JUMP L3\n\
+            JUMP L3\n\
+            L2:\n\
+            INQL _t1 a 1\n\
+            JMPZ L4 _t1\n\
+            JUMP L5\n\
+            L3:\n\
+            JUMP L1\n\
+            JUMP L0\n\
+            L1:\n\
+            HALT",
+            compile_program,
+        );
+    }
+
+    #[should_panic(expected = "Break Outside of Loop or Switch")]
+    #[test]
+    fn test_error_break_outside_of_loop() {
+        compile_program("a: int; { if (a < 1) break; else a = 1; }");
+    }
+
+    #[test]
+    fn test_continue() {
+        compilation_test_template(
+            "a: int; { while (a < 10) { a = a + 1; switch (a) { case 5: continue; default: } output(a); } }",
+            "L0:\n\
+            ILSS _t0 a 10\n\
+            JMPZ L1 _t0\n\
+            IADD _t1 a 1\n\
+            IASN a _t1\n\
+            JUMP L2\n\
+            L4:\n\
+            JUMP L0\n\
+            L5:\n\
+            JUMP L3\n\
+            L2:\n\
+            INQL _t2 a 5\n\
+            JMPZ L4 _t2\n\
+            JUMP L5\n\
+            L3:\n\
+            IPRT a\n\
+            JUMP L0\n\
+            L1:\n\
+            HALT",
+            compile_program,
+        );
+    }
+
+    #[should_panic(expected = "Continue Outside of Loop")]
+    #[test]
+    fn test_error_continue_outside_of_loop() {
+        compile_program("a: int; { switch (a) { default: continue; } }");
+    }
+
+    #[test]
+    fn test_if_without_else() {
+        compilation_test_template(
+            "a: int; { if (a < 1) a = 1; output(a); }",
+            "ILSS _t0 a 1\n\
+            JMPZ L0 _t0\n\
+            IASN a 1\n\
+            L0:\n\
+            IPRT a\n\
+            HALT",
+            compile_program,
+        );
+    }
+
+    #[test]
+    fn test_else_if_chain() {
+        compilation_test_template(
+            "a: int; { if (a < 1) a = 1; else if (a < 2) a = 2; else a = 3; }",
+            "ILSS _t0 a 1\n\
+            JMPZ L0 _t0\n\
+            IASN a 1\n\
+            JUMP L1\n\
+            L0:\n\
+            ILSS _t1 a 2\n\
+            JMPZ L2 _t1\n\
+            IASN a 2\n\
+            JUMP L1\n\
+            L2:\n\
+            IASN a 3\n\
+            L1:\n\
+            HALT",
+            compile_program,
+        );
+    }
+
+    #[test]
+    fn test_do_while() {
+        compilation_test_template(
+            "a: int; { do { a = a + 1; if (a == 3) continue; } while (a < 5); }",
+            "L0:\n\
+            IADD _t0 a 1\n\
+            IASN a _t0\n\
+            IEQL _t1 a 3\n\
+            JMPZ L3 _t1\n\
+            JUMP L1\n\
+            L3:\n\
+            L1:\n\
+            IGRT _t2 a 4\n\
+            JMPZ L0 _t2\n\
+            L2:\n\
+            HALT",
+            compile_program,
+        );
+    }
+
+    #[test]
+    fn test_for() {
+        compilation_test_template(
+            "a, b: int; { for (a = 0; a < 3; a = a + 1) { if (a == 1) continue; b = b + a; } }",
+            "IASN a 0\n\
+            L0:\n\
+            ILSS _t1 a 3\n\
+            JMPZ L2 _t1\n\
+            IEQL _t2 a 1\n\
+            JMPZ L3 _t2\n\
+            JUMP L1\n\
+            L3:\n\
+            IADD _t3 b a\n\
+            IASN b _t3\n\
+            L1:\n\
+            IADD _t0 a 1\n\
+            IASN a _t0\n\
+            JUMP L0\n\
+            L2:\n\
+            HALT",
+            compile_program,
+        );
+    }
+
+    #[should_panic(expected = "In the initialization of a for loop")]
+    #[test]
+    fn test_error_for_init() {
+        compile_program("a: int; { for (a = 0.5; a < 3; a = a + 1) output(a); }");
+    }
+
+    #[test]
+    fn test_labeled_loops() {
+        compilation_test_template(
+            "a, b: int; { outer: while (a < 3) { for (b = 0; b < 3; b = b + 1) { if (b == a) continue outer; if (b > a) break outer; } } }",
+            "L0:\n\
+            ILSS _t0 a 3\n\
+            JMPZ L1 _t0\n\
+            IASN b 0\n\
+            L2:\n\
+            ILSS _t2 b 3\n\
+            JMPZ L4 _t2\n\
+            IEQL _t3 b a\n\
+            JMPZ L5 _t3\n\
+            JUMP L0\n\
+            L5:\n\
+            IGRT _t4 b a\n\
+            JMPZ L7 _t4\n\
+            JUMP L1\n\
+            L7:\n\
+            L3:\n\
+            IADD _t1 b 1\n\
+            IASN b _t1\n\
+            JUMP L2\n\
+            L4:\n\
+            JUMP L0\n\
+            L1:\n\
+            HALT",
+            compile_program,
+        );
+    }
+
+    #[should_panic(expected = "Unknown Loop Label")]
+    #[test]
+    fn test_error_unknown_loop_label() {
+        compile_program("a: int; { outer: while (a < 3) { while (a < 2) break inner; } }");
+    }
+
+    #[should_panic(expected = "Shadowed Loop Label")]
+    #[test]
+    fn test_error_shadowed_loop_label() {
+        compile_program("a: int; { outer: while (a < 3) { outer: while (a < 2) break outer; } }");
+    }
+
+    #[test]
+    fn test_compound_assignment() {
+        compilation_test_template(
+            "a: int; f: float; { a += 2 * a; f -= a; a++; f--; }",
+            "IMLT _t0 2 a\n\
+            IADD a a _t0\n\
+            ITOR _t1 a\n\
+            RSUB f f _t1\n\
+            IADD a a 1\n\
+            RSUB f f 1.0\n\
+            HALT",
+            compile_program,
+        );
+    }
+
+    #[should_panic(expected = "Incorrect type in Assignment")]
+    #[test]
+    fn test_error_compound_assignment() {
+        compile_program("a: int; f: float; { a *= f; }");
+    }
+
+    #[test]
+    fn test_arrays() {
+        compilation_test_template(
+            "a[2]: int; i: int; { a[1] = 3; a[i] = a[1]; }",
+            "IASN a__1 3\n\
+            INQL _t0 i 0\n\
+            JMPZ L1 _t0\n\
+            INQL _t1 i 1\n\
+            JMPZ L2 _t1\n\
+            IPRT 999999998\n\
+            IPRT 1\n\
+            JUMP L3\n\
+            L1:\n\
+            IASN a__0 a__1\n\
+            JUMP L0\n\
+            L2:\n\
+            IASN a__1 a__1\n\
+            JUMP L0\n\
+            L0:\n\
+            L3:\n\
+            HALT",
+            compile_program,
+        );
+    }
+
+    #[should_panic(expected = "Index Out of Range")]
+    #[test]
+    fn test_error_index_out_of_range() {
+        compile_program("a[2]: int; { output(a[2]); }");
+    }
+
+    #[should_panic(expected = "Array Without Index")]
+    #[test]
+    fn test_error_array_without_index() {
+        compile_program("a[2]: int; { output(a); }");
+    }
+
+    #[should_panic(expected = "Reserved Name")]
+    #[test]
+    fn test_error_reserved_name() {
+        compile_program("a[2]: int; a__0: int; { a[0] = 5; a__0 = 9; output(a[0]); }");
+    }
+
+    #[test]
+    fn test_functions() {
+        compilation_test_template(
+            "a: int; func f(x: int): float { return x; } { output(f(a) + f(1)); }",
+            "IASN _f__x a\n\
+            IASN _f___site 0\n\
+            JUMP L0\n\
+            L2:\n\
+            RASN _t1 _f___return\n\
+            IASN _f__x 1\n\
+            IASN _f___site 1\n\
+            JUMP L0\n\
+            L3:\n\
+            RASN _t2 _f___return\n\
+            RADD _t3 _t1 _t2\n\
+            RPRT _t3\n\
+            JUMP L4\n\
+            L0:\n\
+            ITOR _t0 _f__x\n\
+            RASN _f___return _t0\n\
+            JUMP L1\n\
+            L1:\n\
+            INQL _t4 _f___site 0\n\
+            JMPZ L2 _t4\n\
+            JUMP L3\n\
+            L4:\n\
+            HALT",
+            compile_program,
+        );
+        // The variables of f_g and of f can't be confused
+        let compiled = compile_program(
+            "func f_g(h: int): int { return h; } func f(g_h: int): int { return f_g(1) + g_h; } { output(f(10)); }",
+        );
+        assert!(
+            compiled.contains("IASN _f_g__h 1\n") && compiled.contains("IADD _t1 _t0 _f__g_h\n")
+        );
+    }
+
+    #[test]
+    fn test_comments() {
+        compilation_test_template(
+            "a: int; // a = 1;\n{ /* a /* = */ 2; */ a = 3; // */\n }",
+            "IASN a 3\nHALT",
+            compile_program,
+        );
+    }
+
+    #[test]
+    fn test_error_unterminated_comment() {
+        let (_, errors) = Lexer::lex(String::from("a: int;\n{ /* a /* */\n a = 3; }"));
+        assert_eq!(errors.len(), 1);
+        assert!(format!("{}", errors[0]).contains("[Line 2, Column 3]"));
+    }
+
+    #[test]
+    fn test_error_unmatched_comment_end() {
+        let (_, errors) = Lexer::lex(String::from("a: int;\n{ /* a */ */\n a = 3; }"));
+        assert_eq!(errors.len(), 1);
+        let error = format!("{}", errors[0]);
+        assert!(error.contains("Unmatched Comment End"));
+        assert!(error.contains("[Line 2, Column 11]"));
+    }
+
+    #[test]
+    fn test_numbers() {
+        compilation_test_template(
+            "0x1F + 0b101 * 1_000 + 7i",
+            "IMLT _t0 5 1000\nIADD _t1 31 _t0\nIADD _t2 _t1 7\n",
+            compile_expression,
+        );
+        compilation_test_template(
+            "1e-7 + .5 * 2f + 1.5E3",
+            "RMLT _t0 0.5 2.0\nRADD _t1 0.0000001 _t0\nRADD _t2 _t1 1500.0\n",
+            compile_expression,
+        );
+        // The smallest int is only in range with its sign
+        compilation_test_template(
+            "{ output(-2147483648); }",
+            "ISUB _t0 0 2147483647\nISUB _t0 _t0 1\nIPRT _t0\nHALT",
+            compile_program,
+        );
+    }
+
+    #[should_panic(expected = "it's too large for an int")]
+    #[test]
+    fn test_error_int_too_small() {
+        compile_program("{ output(-2147483649); }");
+    }
+
+    #[should_panic(expected = "Invalid Number")]
+    #[test]
+    fn test_error_invalid_number() {
+        compile_program("a: int; { a = 1.5i; }");
+    }
+
+    #[test]
+    fn test_conditional() {
+        compilation_test_template(
+            "a: int; x: float; { x = a > 0 ? a : -1.5; output((a == 0 ? 1 : 2) * 3); }",
+            "IGRT _t0 a 0\n\
+            JMPZ L0 _t0\n\
+            ITOR _t1 a\n\
+            RASN _t3 _t1\n\
+            JUMP L1\n\
+            L0:\n\
+            RSUB _t2 0.0 1.5\n\
+            RASN _t3 _t2\n\
+            L1:\n\
+            RASN x _t3\n\
+            IEQL _t4 a 0\n\
+            JMPZ L2 _t4\n\
+            IASN _t5 1\n\
+            JUMP L3\n\
+            L2:\n\
+            IASN _t5 2\n\
+            L3:\n\
+            IMLT _t6 _t5 3\n\
+            IPRT _t6\n\
+            HALT",
+            compile_program,
+        );
+        // The false branch can be another conditional expression
+        compilation_test_template(
+            "a: int; { output(a == 1 ? 1 : a == 2 ? 2 : 3); }",
+            "IEQL _t0 a 1\n\
+            JMPZ L2 _t0\n\
+            IASN _t3 1\n\
+            JUMP L3\n\
+            L2:\n\
+            IEQL _t1 a 2\n\
+            JMPZ L0 _t1\n\
+            IASN _t2 2\n\
+            JUMP L1\n\
+            L0:\n\
+            IASN _t2 3\n\
+            L1:\n\
+            IASN _t3 _t2\n\
+            L3:\n\
+            IPRT _t3\n\
+            HALT",
+            compile_program,
+        );
+    }
+
+    #[test]
+    fn test_constants() {
+        compilation_test_template(
+            "const N: int = 7 % -2 * 3; const F: float = N + 0.5; a: float; { a = F * 2; output(N); }",
+            "RSUB _t1 0.0 2.5\n\
+            ITOR _t0 2\n\
+            RMLT _t2 _t1 _t0\n\
+            RASN a _t2\n\
+            ISUB _t3 0 3\n\
+            IPRT _t3\n\
+            HALT",
+            compile_program,
+        );
+        compilation_test_template(
+            "const N: int = 7; const B: bool = N > 5 && !(N == 3) || N < 5.5; { output(B); }",
+            "IPRT 1\nHALT",
+            compile_program,
+        );
+    }
+
+    #[should_panic(expected = "Constant Overflow")]
+    #[test]
+    fn test_error_constant_overflow() {
+        compile_program("const X: int = 2147483647 + 1; { }");
+    }
+
+    #[test]
+    fn test_error_in_constant() {
+        // The constant is still declared, so using it isn't another error
+        let errors = Parser::new(
+            Lexer::lex(String::from(
+                "a: int; const N: int = a + 1; const M: int = N; { output(N); }",
+            ))
+            .0,
+        )
+        .parse_program()
+        .unwrap_err();
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn test_initializers() {
+        compilation_test_template(
+            "const N: int = 3; a, b[2]: int = N * 2; x: float = -1; { c: bool = true; output(x); }",
+            "IASN a 6\n\
+            IASN b__0 6\n\
+            IASN b__1 6\n\
+            RSUB _t0 0.0 1.0\n\
+            RASN x _t0\n\
+            IASN __b0__c 1\n\
+            RPRT x\n\
+            HALT",
+            compile_program,
+        );
+    }
+
+    #[should_panic(expected = "Incorrect type in Assignment")]
+    #[test]
+    fn test_error_initializer_type() {
+        compile_program("a: int = 1.5; { }");
+    }
+
+    #[should_panic(expected = "Assignment to Constant")]
+    #[test]
+    fn test_error_assignment_to_constant() {
+        compile_program("const N: int = 1; { N += 1; }");
+    }
+
+    #[test]
+    fn test_case_labels() {
+        compilation_test_template(
+            "const N: int = 2; a: int; { switch (a) { case -1: output(1); case N: output(2); default: output(0); } }",
+            "JUMP L0\n\
+            L2:\n\
+            IPRT 1\n\
+            L3:\n\
+            IPRT 2\n\
+            L4:\n\
+            IPRT 0\n\
+            JUMP L1\n\
+            L0:\n\
+            ISUB _t0 0 1\n\
+            INQL _t1 a _t0\n\
+            JMPZ L2 _t1\n\
+            INQL _t2 a 2\n\
+            JMPZ L3 _t2\n\
+            JUMP L4\n\
+            L1:\n\
+            HALT",
+            compile_program,
+        );
+    }
+
+    #[should_panic(expected = "Not a Constant Expression")]
+    #[test]
+    fn test_error_case_not_a_constant() {
+        compile_program("a: int; { switch (a) { case a: default: } }");
+    }
+
+    #[should_panic(expected = "Not a Constant Expression")]
+    #[test]
+    fn test_error_not_a_constant() {
+        compile_program("a: int; const N: int = a + 1; { }");
+    }
+
+    #[should_panic(expected = "Duplicate Declaration")]
+    #[test]
+    fn test_error_duplicate_declaration() {
+        compile_program("const N: int = 1; N: int; { N = 2; }");
+    }
+
+    #[test]
+    fn test_intrinsics() {
+        compilation_test_template(
+            "a, b: int; { output(max(a, abs(b))); }",
+            "ILSS _t0 b 0\n\
+            JMPZ L0 _t0\n\
+            ISUB _t1 0 b\n\
+            IASN _t2 _t1\n\
+            JUMP L1\n\
+            L0:\n\
+            IASN _t2 b\n\
+            L1:\n\
+            IGRT _t3 a _t2\n\
+            JMPZ L2 _t3\n\
+            IASN _t4 a\n\
+            JUMP L3\n\
+            L2:\n\
+            IASN _t4 _t2\n\
+            L3:\n\
+            IPRT _t4\n\
+            HALT",
+            compile_program,
+        );
+    }
+
+    #[test]
+    fn test_intrinsic_subroutines() {
+        let compiled = compile_program("x: float; { x = sqrt(x) + sqrt(2); }");
+        // Both calls jump to the same subroutine, which is generated once, and abs is generated inline
+        assert_eq!(compiled.matches("JUMP L0\n").count(), 2);
+        assert_eq!(compiled.matches("\nL0:\n").count(), 1);
+        assert!(!compile_program("x: float; { x = abs(x); }").contains("__site"));
+    }
+
+    #[should_panic(expected = "Provided Incorrect type in Argument")]
+    #[test]
+    fn test_error_pow_exponent_type() {
+        compile_program("x: float; { x = pow(2, x); }");
+    }
+
+    #[test]
+    fn test_bitwise() {
+        compilation_test_template(
+            "a, n: int; { a = (a >> 1) + ~a + (1 << 4 | 3 ^ 1 & 7) + (a << n); }",
+            "IDIV _t0 a 2\n\
+            ISUB _t1 0 a\n\
+            ISUB _t2 _t1 1\n\
+            IADD _t3 _t0 _t2\n\
+            IADD _t4 _t3 18\n\
+            IASN _t5 1\n\
+            IASN _t6 n\n\
+            L0:\n\
+            IGRT _t7 _t6 0\n\
+            JMPZ L1 _t7\n\
+            IMLT _t5 _t5 2\n\
+            ISUB _t6 _t6 1\n\
+            JUMP L0\n\
+            L1:\n\
+            IMLT _t8 a _t5\n\
+            IADD _t9 _t4 _t8\n\
+            IASN a _t9\n\
+            HALT",
+            compile_program,
+        );
+        // && and || are still lexed as logical operators
+        let tokens: Vec<Token> = Lexer::lex(String::from("a&&b&c||d|e<<f<g"))
+            .0
+            .iter()
+            .map(|lexed_token| lexed_token.token)
+            .collect();
+        assert!(tokens[1] == AND_TOK && tokens[3] == BITAND_TOK && tokens[5] == OR_TOK);
+        assert!(tokens[7] == BITOR_TOK && tokens[9] == SHIFTOP_TOK && tokens[11] == RELOP_TOK);
+    }
+
+    #[should_panic(expected = "Provided Incorrect type for Operator")]
+    #[test]
+    fn test_error_bitwise_float() {
+        compile_program("a: int; { a = a | 1.5; }");
+    }
+
+    #[test]
+    fn test_asm() {
+        compilation_test_template(
+            "const K: int = -2; a: int; { a = 1; { b: int; asm {\n top: IADD b a K\n JMPZ top b\n HALT } } }",
+            "IASN a 1\n\
+            L0:\n\
+            ISUB _t0 0 2\n\
+            IADD __b1__b a _t0\n\
+            JMPZ L0 __b1__b\n\
+            JUMP L1\n\
+            L1:\n\
+            HALT",
+            compile_program,
+        );
+    }
+
+    #[should_panic(expected = "Invalid Operand")]
+    #[test]
+    fn test_error_asm_operand_type() {
+        compile_program("x: int; { asm { RADD x x 1.0 } }");
+    }
+
+    #[test]
+    fn test_error_asm_undefined_label() {
+        // Reported where the label is used, not at the end of the block
+        let errors = Parser::new(Lexer::lex(String::from("x: int; { asm {\n JMPZ end x\n } }")).0)
+            .parse_program()
+            .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        let error = format!("{}", errors[0]);
+        assert!(error.contains("Undefined Label"));
+        assert!(error.contains("[Line 2, Column 9]"));
+    }
+
+    #[should_panic(expected = "Quad's interpreter can't read a negative literal")]
+    #[test]
+    fn test_error_asm_negative_literal() {
+        compile_program("x: int; { asm { IADD x x -1 } }");
+    }
+
+    #[should_panic(expected = "Reserved Name")]
+    #[test]
+    fn test_error_asm_reserved_name() {
+        compile_program("func f(x: int) { output(x); } { f(1); asm { IPRT f__x } }");
+    }
+
+    #[test]
+    fn test_block_scopes() {
+        let program = "a: int; { a = 1; { a: float; b: int; a = 2.5; b = 3; } { b: int; b = a; } }";
+        let mut parser = Parser::new(Lexer::lex(String::from(program)).0);
+        assert_eq!(
+            parser.parse_program().unwrap(),
+            "IASN a 1\n\
+            RASN __b1__a 2.5\n\
+            IASN __b1__b 3\n\
+            IASN __b2__b a\n\
+            HALT"
+        );
+        // Only the inner a hides a name
+        assert_eq!(parser.warnings().len(), 1);
+        // The variables of a block can't be confused with the variables of a function
+        let compiled = compile_program(
+            "func b1(x: int): int { return x + 1; } { x: int; x = 5; output(b1(10)); output(x); }",
+        );
+        assert!(compiled.starts_with("IASN __b1__x 5\nIASN _b1__x 10\n"));
+    }
+
+    #[test]
+    fn test_recursion() {
+        compilation_test_template(
+            "func f(n: int): int { if (n > 0) return f(n - 1); return n; } { output(f(1)); }",
+            "IASN _f___depth 0\n\
+            IASN _f__n 1\n\
+            IASN _f___site 1\n\
+            JUMP L0\n\
+            L16:\n\
+            IASN _t4 _f___return\n\
+            IPRT _t4\n\
+            JUMP L13\n\
+            L0:\n\
+            IASN _t0 0\n\
+            IASN _t1 0\n\
+            IASN _t2 0\n\
+            IGRT _t0 _f__n 0\n\
+            JMPZ L2 _t0\n\
+            ISUB _t1 _f__n 1\n\
+            IASN _f___frame_site 0\n\
+            JUMP L4\n\
+            L6:\n\
+            IASN _f__n _t1\n\
+            IASN _f___site 0\n\
+            JUMP L0\n\
+            L8:\n\
+            IASN _f___frame_site 0\n\
+            JUMP L5\n\
+            L7:\n\
+            IASN _t2 _f___return\n\
+            IASN _f___return _t2\n\
+            JUMP L1\n\
+            L2:\n\
+            IASN _f___return _f__n\n\
+            JUMP L1\n\
+            L4:\n\
+            INQL _t3 _f___depth 0\n\
+            JMPZ L10 _t3\n\
+            IPRT 999999997\n\
+            JUMP L12\n\
+            L12:\n\
+            IPRT 1\n\
+            JUMP L11\n\
+            L11:\n\
+            JUMP L13\n\
+            L10:\n\
+            IASN _f__n__s0 _f__n\n\
+            IASN _t0__s0 _t0\n\
+            IASN _t1__s0 _t1\n\
+            IASN _t2__s0 _t2\n\
+            IASN _f___site__s0 _f___site\n\
+            JUMP L9\n\
+            L9:\n\
+            IADD _f___depth _f___depth 1\n\
+            JUMP L6\n\
+            L5:\n\
+            ISUB _f___depth _f___depth 1\n\
+            JUMP L15\n\
+            L15:\n\
+            IASN _f__n _f__n__s0\n\
+            IASN _t0 _t0__s0\n\
+            IASN _t1 _t1__s0\n\
+            IASN _t2 _t2__s0\n\
+            IASN _f___site _f___site__s0\n\
+            JUMP L14\n\
+            L14:\n\
+            JUMP L7\n\
+            L1:\n\
+            INQL _t5 _f___site 0\n\
+            JMPZ L8 _t5\n\
+            JUMP L16\n\
+            L13:\n\
+            HALT",
+            |program| {
+                let mut parser = Parser::new(Lexer::lex(String::from(program)).0);
+                parser.code_generator.set_max_call_depth(1);
+                parser.parse_program().unwrap()
+            },
+        );
+    }
+
+    #[should_panic(expected = "Wrong Number of Arguments")]
+    #[test]
+    fn test_error_argument_count() {
+        compile_program("func p(x: int) { output(x); } { p(1, 2); }");
+    }
+
+    #[should_panic(expected = "Procedure in Expression")]
+    #[test]
+    fn test_error_procedure_in_expression() {
+        compile_program("a: int; func p() { return; } { a = p(); }");
+    }
+
+    #[test]
+    fn test_error_recovery_in_switch() {
+        // The error in the last statement of a case doesn't skip the default
+        let errors = Parser::new(
+            Lexer::lex(String::from(
+                "x: int; { switch (x) { case 1: x = ; default: output(x); } }",
+            ))
+            .0,
+        )
+        .parse_program()
+        .unwrap_err();
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[should_panic(expected = "Missing Return")]
+    #[test]
+    fn test_error_missing_return() {
+        compile_program("func f(a: int): int { if (a > 0) return 1; } { output(f(0)); }");
+    }
+
+    #[test]
+    fn test_return_in_both_branches() {
+        let compiled = compile_program(
+            "func f(a: int): int { if (a > 0) { return 1; } else return 2; } { output(f(0)); }",
+        );
+        assert!(compiled.contains("IASN _f___return 2\nJUMP L1\nL3:\nL1:\n"));
+    }
+
+    #[test]
+    fn test_bool() {
+        compilation_test_template(
+            "a: int; b: bool; { b = a > 2; b = !a; output(!(b || false)); }",
+            "IGRT _t0 a 2\n\
+            IASN b _t0\n\
+            INQL _t1 a 0\n\
+            ISUB _t2 1 _t1\n\
+            IASN b _t2\n\
+            IASN _t3 b\n\
+            JMPZ L0 _t3\n\
+            JUMP L1\n\
+            L0:\n\
+            IASN _t3 0\n\
+            L1:\n\
+            ISUB _t4 1 _t3\n\
+            IADD _t5 _t4 0\n\
+            IPRT _t5\n\
+            HALT",
+            compile_program,
+        );
+    }
+
+    #[test]
+    fn test_exit_and_assert() {
+        compilation_test_template(
+            "a: int; {\n assert(a > 0);\n if (a == 1) exit;\n output(a); }",
+            "IGRT _t0 a 0\n\
+            ISUB _t1 1 _t0\n\
+            JMPZ L0 _t1\n\
+            IPRT 999999999\n\
+            IPRT 2\n\
+            JUMP L1\n\
+            L0:\n\
+            IEQL _t2 a 1\n\
+            JMPZ L2 _t2\n\
+            JUMP L1\n\
+            L2:\n\
+            IPRT a\n\
+            L1:\n\
+            HALT",
+            compile_program,
+        );
+    }
+
+    #[should_panic(expected = "Incorrect type in Assignment")]
+    #[test]
+    fn test_error_bool_assignment() {
+        compile_program("a: int; b: bool; { b = a; }");
+    }
+
     fn compilation_test_template(
         to_compile: &str,
         expected: &str,
@@ -191,22 +1168,28 @@ mod tests {
         }
     }
 
+    fn compile_program(program: &str) -> String {
+        return Parser::new(Lexer::lex(String::from(program)).0)
+            .parse_program()
+            .unwrap();
+    }
+
     fn compile_expression(expr: &str) -> String {
-        return Parser::new(Lexer::lex_tokens(String::from(expr)))
+        return Parser::new(Lexer::lex(String::from(expr)).0)
             .parse_expression()
             .unwrap()
             .code_generated;
     }
 
     fn compile_bool_expression(expr: &str) -> String {
-        return Parser::new(Lexer::lex_tokens(String::from(expr)))
+        return Parser::new(Lexer::lex(String::from(expr)).0)
             .parse_boolexpr()
             .unwrap()
             .code_generated;
     }
 
     fn compile_expression_with_variables(expr: &str, vars: &[(String, VarType)]) -> String {
-        let mut parser = Parser::new(Lexer::lex_tokens(String::from(expr)));
+        let mut parser = Parser::new(Lexer::lex(String::from(expr)).0);
         for (var_name, var_type) in vars.into_iter() {
             parser
                 .code_generator
@@ -215,9 +1198,8 @@ mod tests {
         return parser.parse_expression().unwrap().code_generated;
     }
 
-    #[allow(dead_code)]
     fn compile_bool_expression_with_variables(expr: &str, vars: &[(String, VarType)]) -> String {
-        let mut parser = Parser::new(Lexer::lex_tokens(String::from(expr)));
+        let mut parser = Parser::new(Lexer::lex(String::from(expr)).0);
         for (var_name, var_type) in vars.into_iter() {
             parser
                 .code_generator
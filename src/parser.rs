@@ -1,33 +1,67 @@
 use crate::{
+    asm::{AsmBlock, AsmOperand},
     boolexpr::*,
-    codegen::{CodeGenerator, CodeReference, VarType},
+    codegen::{CodeGenerator, CodeReference, ControlContext, Label, VarType},
     error::*,
     expression::{BinaryOp, Expression},
     lexer::{LexedToken, Lexeme},
     token::*,
 };
 
+/// The tokens that a statement can start with.
+const STMT_START_TOKENS: &[Token] = &[
+    ID_TOK,
+    INPUT_TOK,
+    OUTPUT_TOK,
+    IF_TOK,
+    WHILE_TOK,
+    DO_TOK,
+    FOR_TOK,
+    SWITCH_TOK,
+    BREAK_TOK,
+    CONTINUE_TOK,
+    RETURN_TOK,
+    EXIT_TOK,
+    ASSERT_TOK,
+    ASM_TOK,
+    LCURLY_TOK,
+];
+
+/// The target of an assignment / input statement, a variable or an element of an array.
+struct Target {
+    name: Box<str>,
+    index: Option<Expression>,
+}
+
 #[derive(Default)]
 pub struct Parser {
     generated_code: String,
+    // The code of the functions, which comes after the code of the main program.
+    functions_code: String,
     tokens: Vec<LexedToken>,
     ptr: usize,
     pub code_generator: CodeGenerator,
     last_seen_line: usize,
     last_seen_column: usize,
     errors_found: Vec<CompilationError>,
+    warnings_found: Vec<CompilationWarning>,
+    // Whether the last parsed statement returns on every path, see parse_stmt.
+    last_stmt_returns: bool,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<LexedToken>) -> Self {
         return Parser {
             generated_code: String::new(),
+            functions_code: String::new(),
             tokens,
             ptr: 0,
             code_generator: CodeGenerator::new(),
             last_seen_line: 1,
             last_seen_column: 0,
             errors_found: Vec::new(),
+            warnings_found: Vec::new(),
+            last_stmt_returns: false,
         };
     }
 
@@ -56,6 +90,22 @@ impl Parser {
             .map_or(false, |lookahead| tok == lookahead);
     }
 
+    /// Return true if the token after the lookahead token matches the paramter
+    fn is_second_lookahead(&self, tok: Token) -> bool {
+        return self
+            .tokens
+            .get(self.ptr + 1)
+            .map_or(false, |lexed_token| tok == lexed_token.token);
+    }
+
+    /// Get the token n tokens after the lookahead token, without moving to it
+    fn nth_lookahead_tok(&self, n: usize) -> Option<Token> {
+        return self
+            .tokens
+            .get(self.ptr + n)
+            .map(|lexed_token| lexed_token.token);
+    }
+
     /// Match the token, if the next token doesn't match, return a Parsing Error.
     fn match_tok(&mut self, tok: Token) -> Result<Lexeme, CompilationError> {
         let lookahead = self.lookahead()?;
@@ -82,25 +132,57 @@ impl Parser {
         }
     }
 
+    /// Warn if a declaration hides a name of an enclosing scope.
+    fn check_shadowing(&mut self, name: &str) {
+        if self.code_generator.is_shadowing(name) {
+            self.warnings_found
+                .push(CompilationWarning::codegen_warning(
+                    self.last_seen_line,
+                    self.last_seen_column,
+                    CodeGenWarningKind::shadowed_variable(name),
+                ));
+        }
+    }
+
+    /// The warnings found while parsing the program.
+    pub fn warnings(&self) -> &[CompilationWarning] {
+        return &self.warnings_found;
+    }
+
     // ID
     fn parse_id(&mut self) -> Result<Lexeme, CompilationError> {
         return self.match_tok(ID_TOK); // ID
     }
 
     /// declerations stmt_block
-    pub fn parse_program(mut self) -> Result<String, Vec<CompilationError>> {
+    /// declerations functions stmt_block
+    pub fn parse_program(&mut self) -> Result<String, Vec<CompilationError>> {
         let declerations = self.parse_declerations();
         self.cache_error(declerations);
 
+        let functions = self.parse_functions();
+        self.cache_error(functions);
+
         let stmt_block = self.parse_stmt_block();
         self.cache_error(stmt_block);
 
-        self.push_generated_code("HALT");
+        let subroutines_code = self.code_generator.take_subroutines_code();
+        self.functions_code.push_str(&subroutines_code);
+        if !self.functions_code.is_empty() {
+            // The main program ends before the code of the functions
+            let exit_stmt = self.code_generator.gen_exit_stmt();
+            self.push_generated_code(&exit_stmt);
+            let functions_code = std::mem::take(&mut self.functions_code);
+            self.push_generated_code(&functions_code);
+            let function_returns = self.code_generator.gen_function_returns();
+            self.push_generated_code(&function_returns);
+        }
+        self.push_generated_code(&self.code_generator.gen_halt());
 
         if self.errors_found.is_empty() {
-            return Ok(self.generated_code);
+            return Ok(std::mem::take(&mut self.generated_code));
         } else {
-            return Err(self.errors_found);
+            return Err(std::mem::take(&mut self.errors_found));
         }
     }
 
@@ -110,6 +192,42 @@ impl Parser {
             self.parse_decleration()?;
             return self.parse_declerations();
         }
+        if self.is_lookahead(CONST_TOK) {
+            self.parse_const_decleration()?;
+            return self.parse_declerations();
+        }
+        return Ok(());
+    }
+
+    /// CONST ID : type = value ;
+    // The value is computed at compile time, and replaces the constant wherever it's used.
+    fn parse_const_decleration(&mut self) -> Result<(), CompilationError> {
+        self.match_tok(CONST_TOK)?; // const
+        let name = self.parse_id()?.0; // ID
+        self.match_tok(COLON_TOK)?; // :
+        let ty = self.parse_type()?; // type
+        self.match_tok(EQ_TOK)?; // =
+        self.check_shadowing(&name);
+        self.code_generator.set_fold_constants(true);
+        let value = self.parse_value(); // boolexpr | expression
+        self.code_generator.set_fold_constants(false);
+        let value = match value {
+            Ok(value) => value,
+            Err(error) => {
+                // The constant is still declared, so its uses aren't reported as undeclared
+                let _ = self.code_generator.declare_constant(&name, ty, None);
+                return Err(error);
+            }
+        };
+        if let Err(codegen_err) = self.code_generator.declare_constant(&name, ty, Some(value)) {
+            // The constant is declared anyway, so the declarations after it are still compiled
+            self.errors_found.push(CompilationError::codegen_error(
+                self.last_seen_line,
+                self.last_seen_column,
+                codegen_err,
+            ));
+        }
+        self.match_tok(SEMIC_TOK)?; // ;
         return Ok(());
     }
 
@@ -118,14 +236,224 @@ impl Parser {
         let idlist = self.parse_id_list()?;
         self.match_tok(COLON_TOK)?; // :
         let ty = self.parse_type()?;
-        for id in idlist.into_iter().cloned() {
-            self.code_generator.register_variable(Box::leak(id.0), ty);
+        // The initial value is computed before the names are declared, so it can't refer to them.
+        let initial_value = match self.match_tok(EQ_TOK) {
+            // = value
+            Ok(..) => self.parse_initializer(&idlist[0].0 .0, ty).map(Some),
+            Err(..) => Ok(None),
+        };
+        for (id, array_size) in idlist.into_vec() {
+            self.check_shadowing(&id.0);
+            let var_name = match self.code_generator.declare_name(&id.0) {
+                Ok(var_name) => var_name,
+                Err(codegen_err) => {
+                    // The first declaration of the name is kept
+                    self.errors_found.push(CompilationError::codegen_error(
+                        self.last_seen_line,
+                        self.last_seen_column,
+                        codegen_err,
+                    ));
+                    continue;
+                }
+            };
+            match array_size {
+                Some(size) => self.code_generator.register_array(var_name, ty, size),
+                None => self.code_generator.register_variable(var_name, ty),
+            }
+            if let Ok(Some(initial_value)) = &initial_value {
+                let initialization = self
+                    .code_generator
+                    .gen_initialization(var_name, initial_value);
+                self.push_generated_code(&initialization);
+            }
+        }
+        self.match_tok(SEMIC_TOK)?; // ;
+        return initial_value.map(|_| ());
+    }
+
+    // The initial value of a declaration, it must be computed at compile time.
+    fn parse_initializer(
+        &mut self,
+        name: &str,
+        ty: VarType,
+    ) -> Result<Expression, CompilationError> {
+        self.code_generator.set_fold_constants(true);
+        let value = self.parse_value(); // boolexpr | expression
+        self.code_generator.set_fold_constants(false);
+        let value = self
+            .code_generator
+            .constant_value(name, ty, value?)
+            .map_err(|codegen_err| {
+                CompilationError::codegen_error(
+                    self.last_seen_line,
+                    self.last_seen_column,
+                    codegen_err,
+                )
+            })?
+            .materialize(&mut self.code_generator);
+        // A negative value is computed once, before it's copied into every declared variable
+        self.push_generated_code(&value.code_generated);
+        return Ok(value.result());
+    }
+
+    /// functions function | epsilon
+    fn parse_functions(&mut self) -> Result<(), CompilationError> {
+        if self.is_lookahead(FUNC_TOK) {
+            self.parse_function()?;
+            return self.parse_functions();
+        }
+        return Ok(());
+    }
+
+    /// FUNC ID ( params ) declerations stmt_block | FUNC ID ( params ) : type declerations stmt_block
+    fn parse_function(&mut self) -> Result<(), CompilationError> {
+        self.match_tok(FUNC_TOK)?; // func
+        let name = self.parse_id()?.0; // ID
+        self.code_generator
+            .declare_function(Box::leak(name))
+            .map_err(|codegen_err| {
+                CompilationError::codegen_error(
+                    self.last_seen_line,
+                    self.last_seen_column,
+                    codegen_err,
+                )
+            })?;
+        let function = self.parse_function_rest();
+        // The names of the function's parameters and locals can't be used after it, even if it failed to compile.
+        let function_end = self.code_generator.end_function(self.last_stmt_returns);
+        function?;
+        let function_end = function_end.map_err(|codegen_err| {
+            CompilationError::codegen_error(self.last_seen_line, self.last_seen_column, codegen_err)
+        })?;
+        self.functions_code.push_str(&function_end);
+        return Ok(());
+    }
+
+    // The rest of a function after its name, the code of its body is pushed to the functions' code.
+    fn parse_function_rest(&mut self) -> Result<(), CompilationError> {
+        self.match_tok(LPAREN_TOK)?; // (
+        if !self.is_lookahead(RPAREN_TOK) {
+            self.parse_params()?; // params
+        }
+        self.match_tok(RPAREN_TOK)?; // )
+        if let Ok(..) = self.match_tok(COLON_TOK) {
+            // :
+            let return_type = self.parse_type()?; // type
+            self.code_generator.declare_return_type(return_type);
+        }
+        let main_code = std::mem::take(&mut self.generated_code);
+        let body = self.parse_function_body(); // declerations stmt_block
+        let body_code = std::mem::replace(&mut self.generated_code, main_code);
+        // The start of a recursive function depends on its body
+        self.functions_code
+            .push_str(&self.code_generator.gen_function_start()); // Lf:
+        self.functions_code.push_str(&body_code);
+        return body;
+    }
+
+    // The initializations of the function's declarations are part of its body.
+    fn parse_function_body(&mut self) -> Result<(), CompilationError> {
+        self.parse_declerations()?; // declerations
+        return self.parse_stmt_block(); // stmt_block
+    }
+
+    /// params , ID : type | ID : type
+    fn parse_params(&mut self) -> Result<(), CompilationError> {
+        let name = self.parse_id()?.0; // ID
+        self.match_tok(COLON_TOK)?; // :
+        let ty = self.parse_type()?; // type
+        self.check_shadowing(&name);
+        self.code_generator
+            .declare_param(&name, ty)
+            .map_err(|codegen_err| {
+                CompilationError::codegen_error(
+                    self.last_seen_line,
+                    self.last_seen_column,
+                    codegen_err,
+                )
+            })?;
+        if let Ok(..) = self.match_tok(COMMA_TOK) {
+            // ,
+            return self.parse_params();
+        }
+        return Ok(());
+    }
+
+    /// ID ( args )
+    // Returns the code of the call, and the return value if the function has one.
+    fn parse_call(&mut self) -> Result<(String, Option<Expression>), CompilationError> {
+        let name = self.parse_id()?.0; // ID
+        self.match_tok(LPAREN_TOK)?; // (
+        let mut args = Vec::new();
+        if !self.is_lookahead(RPAREN_TOK) {
+            args.push(self.parse_value()?); // boolexpr | expression
+            while let Ok(..) = self.match_tok(COMMA_TOK) {
+                // ,
+                args.push(self.parse_value()?); // boolexpr | expression
+            }
         }
+        self.match_tok(RPAREN_TOK)?; // )
+        return self
+            .code_generator
+            .gen_call(&name, args, self.last_seen_line)
+            .map_err(|codegen_err| {
+                CompilationError::codegen_error(
+                    self.last_seen_line,
+                    self.last_seen_column,
+                    codegen_err,
+                )
+            });
+    }
+
+    /// call ;
+    fn parse_call_stmt(&mut self) -> Result<(), CompilationError> {
+        let (generated_code, _) = self.parse_call()?; // call
+        self.match_tok(SEMIC_TOK)?; // ;
+        self.push_generated_code(&generated_code);
+        return Ok(());
+    }
+
+    /// call
+    fn parse_call_expr(&mut self) -> Result<Expression, CompilationError> {
+        let name = self.lookahead()?.lexeme.0;
+        let (code_generated, result) = self.parse_call()?; // call
+        let Some(result) = result else {
+            return Err(CompilationError::codegen_error(
+                self.last_seen_line,
+                self.last_seen_column,
+                CodeGenErrorKind::procedure_in_expression(&name),
+            ));
+        };
+        return Ok(Expression {
+            code_generated,
+            ..result
+        });
+    }
+
+    /// RETURN ; | RETURN boolexpr ; | RETURN expression ;
+    fn parse_return_stmt(&mut self) -> Result<(), CompilationError> {
+        self.match_tok(RETURN_TOK)?; // return
+        let value = match self.is_lookahead(SEMIC_TOK) {
+            true => None,
+            false => Some(self.parse_value()?), // boolexpr | expression
+        };
         self.match_tok(SEMIC_TOK)?; // ;
+        let generated_code = self
+            .code_generator
+            .gen_return_stmt(value)
+            .map_err(|codegen_err| {
+                CompilationError::codegen_error(
+                    self.last_seen_line,
+                    self.last_seen_column,
+                    codegen_err,
+                )
+            })?;
+        self.push_generated_code(&generated_code);
+        self.last_stmt_returns = true;
         return Ok(());
     }
 
-    /// INT | FLOAT
+    /// INT | FLOAT | BOOL
     fn parse_type(&mut self) -> Result<VarType, CompilationError> {
         let lookahead_tok = self.lookahead_tok()?;
         match lookahead_tok {
@@ -137,55 +465,115 @@ impl Parser {
                 self.match_tok(FLOAT_TOK)?; // float
                 return Ok(VarType::Float);
             }
+            Token::Keyword(Keyword::Bool) => {
+                self.match_tok(BOOL_TOK)?; // bool
+                return Ok(VarType::Bool);
+            }
             _ => {}
         }
 
         return Err(CompilationError::parsing_error(
             self.last_seen_line,
             self.last_seen_column,
-            ParsingErrorKind::unexpected_tok(&[INT_TOK, FLOAT_TOK], lookahead_tok),
+            ParsingErrorKind::unexpected_tok(&[INT_TOK, FLOAT_TOK, BOOL_TOK], lookahead_tok),
         ));
     }
 
-    /// idlist, ID | ID
-    fn parse_id_list(&mut self) -> Result<Box<[Lexeme]>, CompilationError> {
+    /// idlist, declared_id | declared_id
+    fn parse_id_list(&mut self) -> Result<Box<[(Lexeme, Option<usize>)]>, CompilationError> {
         let mut id_list = Vec::new();
-        id_list.push(self.parse_id()?);
+        id_list.push(self.parse_declared_id()?);
         while let Ok(_) = self.match_tok(COMMA_TOK) {
             // ,
-            // ,
-            id_list.push(self.parse_id()?);
+            id_list.push(self.parse_declared_id()?);
         }
         return Ok(id_list.into_boxed_slice());
     }
 
-    /// INPUT ( ID ) ;
+    /// ID | ID [ NUM ]
+    // Returns the name, and the size if it's an array
+    fn parse_declared_id(&mut self) -> Result<(Lexeme, Option<usize>), CompilationError> {
+        let id = self.parse_id()?; // ID
+        if self.match_tok(LBRACKET_TOK).is_err() {
+            return Ok((id, None));
+        }
+        // [
+        let size = self.parse_num_expr(false)?; // NUM
+        self.match_tok(RBRACKET_TOK)?; // ]
+        return match size.code_ref {
+            CodeReference::IntLiteral(size) if size > 0 => Ok((id, Some(size as usize))),
+            size_ref => Err(CompilationError::codegen_error(
+                self.last_seen_line,
+                self.last_seen_column,
+                CodeGenErrorKind::invalid_array_size(&id.0, size_ref),
+            )),
+        };
+    }
+
+    /// ID | ID [ expression ]
+    fn parse_target(&mut self) -> Result<Target, CompilationError> {
+        let id = self.parse_id()?.0; // ID
+        let name = self.code_generator.resolve_name(&id);
+        if self.code_generator.get_constant(&name).is_some() {
+            return Err(CompilationError::codegen_error(
+                self.last_seen_line,
+                self.last_seen_column,
+                CodeGenErrorKind::assignment_to_constant(&id),
+            ));
+        }
+        if self.match_tok(LBRACKET_TOK).is_err() {
+            return Ok(Target { name, index: None });
+        }
+        // [
+        let index = self.parse_expression()?; // expression
+        self.match_tok(RBRACKET_TOK)?; // ]
+        return Ok(Target {
+            name,
+            index: Some(index),
+        });
+    }
+
+    /// Generate the code that accesses the target, `gen_element` generates the access given the variable.
+    fn gen_target_access(
+        &mut self,
+        target: Target,
+        gen_element: impl FnMut(&mut CodeGenerator, &str) -> Result<String, CodeGenErrorKind>,
+    ) -> Result<String, CompilationError> {
+        let mut gen_element = gen_element;
+        let generated_code = match target.index {
+            None => gen_element(&mut self.code_generator, &target.name),
+            Some(index) => self.code_generator.gen_array_access(
+                &target.name,
+                index,
+                self.last_seen_line,
+                gen_element,
+            ),
+        };
+        return generated_code.map_err(|codegen_err| {
+            CompilationError::codegen_error(self.last_seen_line, self.last_seen_column, codegen_err)
+        });
+    }
+
+    /// INPUT ( target ) ;
     fn parse_input_statement(&mut self) -> Result<(), CompilationError> {
         self.match_tok(INPUT_TOK)?; // input
         self.match_tok(LPAREN_TOK)?; // (
-        let var_name = self.parse_id()?.0;
+        let target = self.parse_target()?; // target
         self.match_tok(RPAREN_TOK)?; // )
         self.match_tok(SEMIC_TOK)?; // ;
                                     // Generate the code for the input statement
-        let generated_code =
-            self.code_generator
-                .gen_input_stmt(&var_name)
-                .map_err(|codegen_err| {
-                    CompilationError::codegen_error(
-                        self.last_seen_line,
-                        self.last_seen_column,
-                        codegen_err,
-                    )
-                })?;
+        let generated_code = self.gen_target_access(target, |code_generator, var_name| {
+            code_generator.gen_input_stmt(var_name)
+        })?;
         self.push_generated_code(&generated_code);
         return Ok(());
     }
 
-    /// OUTPUT ( expression ) ;
+    /// OUTPUT ( boolexpr ) ; | OUTPUT ( expression ) ;
     fn parse_output_statement(&mut self) -> Result<(), CompilationError> {
         self.match_tok(OUTPUT_TOK)?; // output
         self.match_tok(LPAREN_TOK)?; // (
-        let expr = self.parse_expression()?; // expression
+        let expr = self.parse_value()?; // boolexpr | expression
         self.match_tok(RPAREN_TOK)?; // )
         self.match_tok(SEMIC_TOK)?; // ;
         let generated_code = self
@@ -205,23 +593,68 @@ impl Parser {
     /// expression ADDOP term | term
     pub fn parse_expression(&mut self) -> Result<Expression, CompilationError> {
         let term = self.parse_term()?;
+        return self.parse_expression_rest(term);
+    }
+
+    // The rest of an expression after its first term
+    fn parse_expression_rest(&mut self, expr: Expression) -> Result<Expression, CompilationError> {
         if let Ok(addop) = self.match_tok(ADDOP_TOK) {
             // + | -
             let binop = BinaryOp::from_lexeme(addop);
-            return Ok(Expression::binary_op(
-                term,
-                self.parse_expression()?,
-                binop,
-                &mut self.code_generator,
-            ));
+            let term = self.parse_term()?;
+            let expr = Expression::binary_op(expr, term, binop, &mut self.code_generator);
+            return self.parse_expression_rest(expr);
         }
 
-        return Ok(term);
+        return Ok(expr);
+    }
+
+    /// boolexpr | bitexpr | boolexpr ? bitexpr : value
+    // Used wherever a value is expected, if it is a boolean expression, its type is Bool.
+    pub fn parse_value(&mut self) -> Result<Expression, CompilationError> {
+        let relation = self.parse_relation()?; // bitexpr RELOP bitexpr | bitexpr
+        let value = if !self.is_lookahead(AND_TOK) && !self.is_lookahead(OR_TOK) {
+            relation
+        } else {
+            // The relation is the first boolfactor of a boolexpr
+            let factor = self.as_boolfactor(relation)?;
+            let term = self.parse_boolterm_rest(factor)?;
+            self.parse_boolexpr_rest(term)?.as_expression()
+        };
+        if !self.is_lookahead(QUESTION_TOK) {
+            return Ok(value);
+        }
+        let condition = self.as_boolfactor(value)?;
+        return self.parse_conditional_rest(condition);
+    }
+
+    // The rest of a conditional expression after its condition
+    // ? bitexpr : value
+    // Like in C, the false branch can be another conditional expression, so c ? 1 : d ? 2 : 3 is c ? 1 : (d ? 2 : 3).
+    fn parse_conditional_rest(
+        &mut self,
+        condition: BoolExpr,
+    ) -> Result<Expression, CompilationError> {
+        self.match_tok(QUESTION_TOK)?; // ?
+        let if_true = self.parse_bitexpr()?; // bitexpr
+        self.match_tok(COLON_TOK)?; // :
+        let if_false = self.parse_value()?; // value
+        return Ok(Expression::conditional(
+            condition,
+            if_true,
+            if_false,
+            &mut self.code_generator,
+        ));
     }
 
     /// boolexpr OR boolterm | boolterm
     pub fn parse_boolexpr(&mut self) -> Result<BoolExpr, CompilationError> {
         let term = self.parse_boolterm()?;
+        return self.parse_boolexpr_rest(term);
+    }
+
+    // The rest of a boolexpr after its first boolterm
+    fn parse_boolexpr_rest(&mut self, term: BoolExpr) -> Result<BoolExpr, CompilationError> {
         if let Ok(..) = self.match_tok(OR_TOK) {
             // ||
             return Ok(BoolExpr::or(
@@ -234,9 +667,71 @@ impl Parser {
         return Ok(term);
     }
 
+    /// boolexpr OR boolterm | boolterm
+    // The condition of a statement, its code is generated by the statement, see Condition.
+    fn parse_condition(&mut self) -> Result<Condition, CompilationError> {
+        let term = self.parse_condition_term()?;
+        return self.parse_condition_rest(Condition { terms: vec![term] });
+    }
+
+    // The rest of a condition after its first boolterm
+    fn parse_condition_rest(
+        &mut self,
+        mut condition: Condition,
+    ) -> Result<Condition, CompilationError> {
+        if let Ok(..) = self.match_tok(OR_TOK) {
+            // ||
+            condition.terms.push(self.parse_condition_term()?);
+            return self.parse_condition_rest(condition);
+        }
+
+        return Ok(condition);
+    }
+
+    /// boolterm AND boolfactor | boolfactor
+    // A boolterm of a condition, its boolfactors are jumped over by the statement.
+    fn parse_condition_term(&mut self) -> Result<Vec<ConditionFactor>, CompilationError> {
+        let factor = self.parse_condition_factor()?;
+        return self.parse_condition_term_rest(vec![factor]);
+    }
+
+    // The rest of a boolterm of a condition after its first boolfactor
+    fn parse_condition_term_rest(
+        &mut self,
+        mut term: Vec<ConditionFactor>,
+    ) -> Result<Vec<ConditionFactor>, CompilationError> {
+        if let Ok(..) = self.match_tok(AND_TOK) {
+            // &&
+            term.push(self.parse_condition_factor()?);
+            return self.parse_condition_term_rest(term);
+        }
+
+        return Ok(term);
+    }
+
+    /// bitexpr RELOP bitexpr | bitexpr (of type bool)
+    // A boolfactor of a condition, a relation is compiled by the statement, see ConditionFactor.
+    fn parse_condition_factor(&mut self) -> Result<ConditionFactor, CompilationError> {
+        let expr1 = self.parse_bitexpr()?; // bitexpr
+        let Ok(relop_lexeme) = self.match_tok(RELOP_TOK) else {
+            return Ok(ConditionFactor::Value(self.as_boolfactor(expr1)?));
+        }; // > | < | == | != || <= || >=
+        let expr2 = self.parse_bitexpr()?; // bitexpr
+        return Ok(ConditionFactor::Relation(
+            expr1,
+            RelOp::from_lexeme(relop_lexeme),
+            expr2,
+        ));
+    }
+
     /// boolterm AND boolfactor | boolfactor
     fn parse_boolterm(&mut self) -> Result<BoolExpr, CompilationError> {
         let factor = self.parse_boolfactor()?;
+        return self.parse_boolterm_rest(factor);
+    }
+
+    // The rest of a boolterm after its first boolfactor
+    fn parse_boolterm_rest(&mut self, factor: BoolExpr) -> Result<BoolExpr, CompilationError> {
         if let Ok(..) = self.match_tok(AND_TOK) {
             // &&
             return Ok(BoolExpr::and(
@@ -249,65 +744,196 @@ impl Parser {
         return Ok(factor);
     }
 
+    /// bitexpr BITOR bitxor | bitxor
+    // The bitwise operators are left associative, and bind tighter than the relational operators.
+    fn parse_bitexpr(&mut self) -> Result<Expression, CompilationError> {
+        let bitxor = self.parse_bitxor()?;
+        return self.parse_bitexpr_rest(bitxor);
+    }
+
+    // The rest of a bitexpr after its first bitxor
+    fn parse_bitexpr_rest(&mut self, bitexpr: Expression) -> Result<Expression, CompilationError> {
+        if let Ok(bitor) = self.match_tok(BITOR_TOK) {
+            // |
+            let bitxor = self.parse_bitxor()?;
+            let bitexpr = self.gen_binary_op(bitexpr, bitxor, BinaryOp::from_lexeme(bitor))?;
+            return self.parse_bitexpr_rest(bitexpr);
+        }
+
+        return Ok(bitexpr);
+    }
+
+    /// bitxor BITXOR bitand | bitand
+    fn parse_bitxor(&mut self) -> Result<Expression, CompilationError> {
+        let bitand = self.parse_bitand()?;
+        return self.parse_bitxor_rest(bitand);
+    }
+
+    // The rest of a bitxor after its first bitand
+    fn parse_bitxor_rest(&mut self, bitxor: Expression) -> Result<Expression, CompilationError> {
+        if let Ok(bitxor_lexeme) = self.match_tok(BITXOR_TOK) {
+            // ^
+            let bitand = self.parse_bitand()?;
+            let bitxor =
+                self.gen_binary_op(bitxor, bitand, BinaryOp::from_lexeme(bitxor_lexeme))?;
+            return self.parse_bitxor_rest(bitxor);
+        }
+
+        return Ok(bitxor);
+    }
+
+    /// bitand BITAND shift | shift
+    fn parse_bitand(&mut self) -> Result<Expression, CompilationError> {
+        let shift = self.parse_shift()?;
+        return self.parse_bitand_rest(shift);
+    }
+
+    // The rest of a bitand after its first shift
+    fn parse_bitand_rest(&mut self, bitand: Expression) -> Result<Expression, CompilationError> {
+        if let Ok(bitand_lexeme) = self.match_tok(BITAND_TOK) {
+            // &
+            let shift = self.parse_shift()?;
+            let bitand = self.gen_binary_op(bitand, shift, BinaryOp::from_lexeme(bitand_lexeme))?;
+            return self.parse_bitand_rest(bitand);
+        }
+
+        return Ok(bitand);
+    }
+
+    /// shift SHIFTOP expression | expression
+    fn parse_shift(&mut self) -> Result<Expression, CompilationError> {
+        let expr = self.parse_expression()?;
+        return self.parse_shift_rest(expr);
+    }
+
+    // The rest of a shift after its first expression
+    fn parse_shift_rest(&mut self, shift: Expression) -> Result<Expression, CompilationError> {
+        if let Ok(shiftop) = self.match_tok(SHIFTOP_TOK) {
+            // << | >>
+            let expr = self.parse_expression()?;
+            let shift = self.gen_binary_op(shift, expr, BinaryOp::from_lexeme(shiftop))?;
+            return self.parse_shift_rest(shift);
+        }
+
+        return Ok(shift);
+    }
+
     /// term MULOP factor | factor
     fn parse_term(&mut self) -> Result<Expression, CompilationError> {
         let factor = self.parse_factor()?;
+        return self.parse_term_rest(factor);
+    }
+
+    // The rest of a term after its first factor
+    fn parse_term_rest(&mut self, term: Expression) -> Result<Expression, CompilationError> {
         if let Ok(mulop) = self.match_tok(MULOP_TOK) {
-            // * | /
+            // * | / | %
             let binop = BinaryOp::from_lexeme(mulop);
-            return Ok(Expression::binary_op(
-                factor,
-                self.parse_term()?,
-                binop,
-                &mut self.code_generator,
-            ));
+            let factor = self.parse_factor()?;
+            let term = self.gen_binary_op(term, factor, binop)?;
+            return self.parse_term_rest(term);
         }
 
-        return Ok(factor);
+        return Ok(term);
+    }
+
+    // A binary operation, after making sure it can be applied to the types of the expressions
+    fn gen_binary_op(
+        &mut self,
+        expr1: Expression,
+        expr2: Expression,
+        binop: BinaryOp,
+    ) -> Result<Expression, CompilationError> {
+        binop
+            .check_operand_types(&expr1, &expr2)
+            .map_err(|codegen_err| {
+                CompilationError::codegen_error(
+                    self.last_seen_line,
+                    self.last_seen_column,
+                    codegen_err,
+                )
+            })?;
+        return Ok(Expression::binary_op(
+            expr1,
+            expr2,
+            binop,
+            &mut self.code_generator,
+        ));
     }
 
-    /// expression RELOP expression | NOT ( boolexpr )
+    /// bitexpr RELOP bitexpr | bitexpr (of type bool)
     fn parse_boolfactor(&mut self) -> Result<BoolExpr, CompilationError> {
-        let lookahead = self.lookahead_tok()?;
-        match lookahead {
-            NOT_TOK => {
-                self.match_tok(NOT_TOK)?; // not
-                self.match_tok(LPAREN_TOK)?; // (
-                let bool_expr = self.parse_boolexpr()?;
-                self.match_tok(RPAREN_TOK)?; // )
-                return Ok(bool_expr);
-            }
-            _ => (),
+        let relation = self.parse_relation()?; // bitexpr RELOP bitexpr | bitexpr
+        return self.as_boolfactor(relation);
+    }
+
+    /// Make sure the expression can be used as a boolfactor, meaning its type is Bool.
+    fn as_boolfactor(&mut self, expr: Expression) -> Result<BoolExpr, CompilationError> {
+        if expr.ty != VarType::Bool {
+            // A non-bool expression is only a boolfactor if it's followed by a relop.
+            let lookahead_tok = self.lookahead_tok()?;
+            return Err(CompilationError::parsing_error(
+                self.last_seen_line,
+                self.last_seen_column,
+                ParsingErrorKind::unexpected_tok(&[RELOP_TOK], lookahead_tok),
+            ));
         }
+        return Ok(BoolExpr::from_expression(expr));
+    }
 
-        let expr1 = self.parse_expression()?; // expression
-        let relop_lexeme = self.match_tok(RELOP_TOK)?; // > | < | == | != || <= || >=
-        let expr2 = self.parse_expression()?; // expression
+    /// bitexpr RELOP bitexpr | bitexpr
+    fn parse_relation(&mut self) -> Result<Expression, CompilationError> {
+        let expr1 = self.parse_bitexpr()?; // bitexpr
+        let Ok(relop_lexeme) = self.match_tok(RELOP_TOK) else {
+            return Ok(expr1);
+        }; // > | < | == | != || <= || >=
+        let expr2 = self.parse_bitexpr()?; // bitexpr
 
         return Ok(BoolExpr::relop(
             expr1,
             expr2,
             RelOp::from_lexeme(relop_lexeme),
             &mut self.code_generator,
-        ));
+        )
+        .as_expression());
     }
 
-    /// ( expression ) | CAST ( expression ) | ID | NUM
+    /// ( boolexpr ) | ( expression ) | CAST ( expression ) | NOT factor | BITNOT factor | ADDOP factor | ID | NUM | TRUE | FALSE
     fn parse_factor(&mut self) -> Result<Expression, CompilationError> {
         let lookahead = self.lookahead_tok()?;
         match lookahead {
             CAST_TOK => {
                 return self.parse_cast_expr(); // static_cast<{type}> ( expression )
             }
-            ID_TOK => {
-                return self.parse_id_expr(); // ID
+            NOT_TOK => {
+                return self.parse_not_expr(); // ! factor
             }
-            NUM_TOK => {
-                return self.parse_num_expr(); // {int / float  literal}
+            BITNOT_TOK => {
+                return self.parse_bitnot_expr(); // ~ factor
+            }
+            ADDOP_TOK => {
+                return self.parse_unary_expr(); // + factor | - factor
+            }
+            ID_TOK if self.is_second_lookahead(LPAREN_TOK) => {
+                return self.parse_call_expr(); // ID ( args )
+            }
+            ID_TOK => {
+                return self.parse_id_expr(); // ID
+            }
+            NUM_TOK => {
+                return self.parse_num_expr(false); // {int / float  literal}
+            }
+            TRUE_TOK => {
+                self.match_tok(TRUE_TOK)?; // true
+                return Ok(Expression::bool_literal(true));
+            }
+            FALSE_TOK => {
+                self.match_tok(FALSE_TOK)?; // false
+                return Ok(Expression::bool_literal(false));
             }
             LPAREN_TOK => {
                 self.match_tok(LPAREN_TOK)?; // (
-                let expr = self.parse_expression(); // expression
+                let expr = self.parse_value(); // boolexpr | expression
                 self.match_tok(RPAREN_TOK)?; // )
                 return expr;
             }
@@ -316,7 +942,10 @@ impl Parser {
                     self.last_seen_line,
                     self.last_seen_column,
                     ParsingErrorKind::unexpected_tok(
-                        &[CAST_TOK, ID_TOK, NUM_TOK, LPAREN_TOK],
+                        &[
+                            CAST_TOK, NOT_TOK, BITNOT_TOK, ADDOP_TOK, ID_TOK, NUM_TOK, TRUE_TOK,
+                            FALSE_TOK, LPAREN_TOK,
+                        ],
                         lookahead_tok,
                     ),
                 ))
@@ -324,6 +953,57 @@ impl Parser {
         }
     }
 
+    /// ADDOP factor
+    // Unary plus / minus bind tighter than any binary operator, so -a * b is (-a) * b.
+    fn parse_unary_expr(&mut self) -> Result<Expression, CompilationError> {
+        let addop = self.match_tok(ADDOP_TOK)?; // + | -
+        let negative = BinaryOp::from_lexeme(addop) == BinaryOp::Sub;
+        // The sign is part of a number, so -2147483648 fits in an int
+        if negative && self.is_lookahead(NUM_TOK) {
+            return self.parse_num_expr(true); // NUM
+        }
+        let mut expr = self.parse_factor()?; // factor
+        if expr.ty == VarType::Bool {
+            expr = Expression::cast(VarType::Int, expr, &mut self.code_generator);
+        }
+        return match negative {
+            true => Ok(Expression::negate(expr, &mut self.code_generator)),
+            false => Ok(expr),
+        };
+    }
+
+    /// NOT factor
+    // A non-bool factor is cast to bool first, so !x is true when x is 0, like in C.
+    fn parse_not_expr(&mut self) -> Result<Expression, CompilationError> {
+        self.match_tok(NOT_TOK)?; // !
+        let mut expr = self.parse_factor()?; // factor
+        if expr.ty != VarType::Bool {
+            expr = Expression::cast(VarType::Bool, expr, &mut self.code_generator);
+        }
+        return Ok(
+            BoolExpr::not(BoolExpr::from_expression(expr), &mut self.code_generator)
+                .as_expression(),
+        );
+    }
+
+    /// BITNOT factor
+    // A bool factor is used as an int, like with unary minus.
+    fn parse_bitnot_expr(&mut self) -> Result<Expression, CompilationError> {
+        self.match_tok(BITNOT_TOK)?; // ~
+        let mut expr = self.parse_factor()?; // factor
+        if expr.ty == VarType::Float {
+            return Err(CompilationError::codegen_error(
+                self.last_seen_line,
+                self.last_seen_column,
+                CodeGenErrorKind::invalid_operand_type("~", expr.code_ref, expr.ty),
+            ));
+        }
+        if expr.ty == VarType::Bool {
+            expr = Expression::cast(VarType::Int, expr, &mut self.code_generator);
+        }
+        return Ok(Expression::bitwise_not(expr, &mut self.code_generator));
+    }
+
     /// CAST ( expression )
     fn parse_cast_expr(&mut self) -> Result<Expression, CompilationError> {
         let cast_lexeme = self.match_tok(CAST_TOK)?; // static_cast<{type}>
@@ -331,6 +1011,7 @@ impl Parser {
         match &*cast_lexeme.0 {
             "static_cast<int>" => cast_type = VarType::Int,
             "static_cast<float>" => cast_type = VarType::Float,
+            "static_cast<bool>" => cast_type = VarType::Bool,
             lexeme => {
                 return Err(CompilationError::internal_error(format!(
                     "Lexer mistakeingly parsed {} as CAST token. Line {} Column {}",
@@ -340,7 +1021,7 @@ impl Parser {
         }
 
         self.match_tok(LPAREN_TOK)?; // (
-        let expr_to_cast = self.parse_expression()?; // expression
+        let expr_to_cast = self.parse_value()?; // boolexpr | expression
         self.match_tok(RPAREN_TOK)?; // )
 
         if expr_to_cast.ty == cast_type {
@@ -357,6 +1038,13 @@ impl Parser {
     /// ID (variable name)
     fn parse_id_expr(&mut self) -> Result<Expression, CompilationError> {
         let var_name = self.match_tok(ID_TOK)?.0; // ID
+        let var_name = self.code_generator.resolve_name(&var_name);
+        if self.is_lookahead(LBRACKET_TOK) {
+            return self.parse_element_expr(var_name); // ID [ expression ]
+        }
+        if let Some(value) = self.code_generator.get_constant(&var_name) {
+            return Ok(value);
+        }
         let var_type = self
             .code_generator
             .get_var_type(&var_name)
@@ -370,53 +1058,214 @@ impl Parser {
         return Ok(Expression::variable(var_name, var_type));
     }
 
-    /// digit+(.digit+)?
-    fn parse_num_expr(&mut self) -> Result<Expression, CompilationError> {
+    /// ID [ expression ]
+    // An element at a constant index is just a variable, otherwise it's copied into a temporary variable.
+    fn parse_element_expr(&mut self, array_name: Box<str>) -> Result<Expression, CompilationError> {
+        self.match_tok(LBRACKET_TOK)?; // [
+        let index = self.parse_expression()?; // expression
+        self.match_tok(RBRACKET_TOK)?; // ]
+        let to_compilation_error = |parser: &Self, codegen_err| {
+            CompilationError::codegen_error(
+                parser.last_seen_line,
+                parser.last_seen_column,
+                codegen_err,
+            )
+        };
+        let (ty, _) = self
+            .code_generator
+            .get_array(&array_name)
+            .map_err(|codegen_err| to_compilation_error(self, codegen_err))?;
+        if let CodeReference::IntLiteral(index) = index.code_ref {
+            let element_name = self
+                .code_generator
+                .array_element(&array_name, index)
+                .map_err(|codegen_err| to_compilation_error(self, codegen_err))?;
+            return Ok(Expression::variable(element_name, ty));
+        }
+        let element = self.code_generator.new_tmp_var(ty);
+        let code_generated = self
+            .code_generator
+            .gen_array_access(
+                &array_name,
+                index,
+                self.last_seen_line,
+                |code_generator, var_name| {
+                    Ok(code_generator.gen_copy(
+                        ty,
+                        &element,
+                        &CodeReference::VarName(Box::from(var_name)),
+                    ))
+                },
+            )
+            .map_err(|codegen_err| to_compilation_error(self, codegen_err))?;
+        return Ok(Expression {
+            ty,
+            code_ref: element,
+            code_generated,
+        });
+    }
+
+    /// digit+ | digit+ . digit* | . digit+ (with an optional exponent: e[+-]digit+) | 0x hexdigit+ | 0b bindigit+
+    /// Digits can be separated by _, and a number can end with i (int) or f (float).
+    /// A negative number is range checked with its sign.
+    fn parse_num_expr(&mut self, negative: bool) -> Result<Expression, CompilationError> {
         let raw_num_str = self.match_tok(NUM_TOK)?.0; // {int / float literal}
         let raw_num_str = raw_num_str.trim();
-        if raw_num_str.contains(".") {
-            // Parse as a float
-            //  TODO: don't panic, return an error
-            let parsed_num: f32 = raw_num_str
-                .parse()
-                .unwrap_or_else(|_| panic!("Could not parse float literal: {}.", raw_num_str));
-            return Ok(Expression::float_literal(parsed_num));
-        } else {
-            // Parse as an int
-            //  TODO: don't panic, return an error
-            let parsed_num: i32 = raw_num_str
-                .parse()
-                .unwrap_or_else(|_| panic!("Could not parse int literal: {}.", raw_num_str));
-            return Ok(Expression::int_literal(parsed_num));
+        return Self::num_literal(raw_num_str, negative).map_err(|reason| {
+            CompilationError::parsing_error(
+                self.last_seen_line,
+                self.last_seen_column,
+                ParsingErrorKind::invalid_number(raw_num_str, reason),
+            )
+        });
+    }
+
+    // The value of a number (negated if negative), or the reason it's malformed
+    fn num_literal(literal: &str, negative: bool) -> Result<Expression, &'static str> {
+        let (radix, digits) = match literal.get(..2) {
+            Some("0x" | "0X") => (16, &literal[2..]),
+            Some("0b" | "0B") => (2, &literal[2..]),
+            _ => (10, literal),
+        };
+        // f is a hexadecimal digit, so a hexadecimal number can't be a float
+        let (digits, suffix) = match digits.char_indices().last() {
+            Some((i, suffix @ 'i')) => (&digits[..i], Some(suffix)),
+            Some((i, suffix @ 'f')) if radix == 10 => (&digits[..i], Some(suffix)),
+            _ => (digits, None),
+        };
+        let is_digit = |c: Option<char>| c.is_some_and(|c| c.is_digit(radix));
+        for (i, c) in digits.char_indices() {
+            if c == '_'
+                && !(is_digit(digits[..i].chars().last())
+                    && is_digit(digits[i + 1..].chars().next()))
+            {
+                return Err("a _ can only separate two digits");
+            }
+        }
+        let digits = digits.replace('_', "");
+        let sign = if negative { "-" } else { "" };
+
+        if radix != 10 {
+            if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+                return Err(match radix {
+                    16 => "a hexadecimal number can only have the digits 0-9 and a-f",
+                    _ => "a binary number can only have the digits 0 and 1",
+                });
+            }
+            return i32::from_str_radix(&format!("{sign}{digits}"), radix)
+                .map(Expression::int_literal)
+                .map_err(|_| "it's too large for an int");
+        }
+
+        let (mantissa, exponent) = match digits.find(['e', 'E']) {
+            Some(i) => (&digits[..i], Some(&digits[i + 1..])),
+            None => (&digits[..], None),
+        };
+        if mantissa.matches('.').count() > 1
+            || !mantissa.chars().all(|c| c.is_ascii_digit() || c == '.')
+            || !mantissa.chars().any(|c| c.is_ascii_digit())
+        {
+            return Err("expected digits, with an optional decimal point");
+        }
+        if let Some(exponent) = exponent {
+            let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+            if exponent.is_empty() || !exponent.chars().all(|c| c.is_ascii_digit()) {
+                return Err("expected the digits of the exponent after e");
+            }
+        }
+
+        let has_fraction = mantissa.contains('.') || exponent.is_some();
+        let is_float = match suffix {
+            Some('i') if has_fraction => {
+                return Err("an int can't have a decimal point or an exponent")
+            }
+            Some('f') => true,
+            _ => has_fraction,
+        };
+        if is_float {
+            return match format!("{sign}{digits}").parse::<f32>() {
+                Ok(num) if num.is_finite() => Ok(Expression::float_literal(num)),
+                _ => Err("it's too large for a float"),
+            };
         }
+        return format!("{sign}{digits}")
+            .parse::<i32>()
+            .map(Expression::int_literal)
+            .map_err(|_| "it's too large for an int");
     }
 
-    /// ID = expression ;
+    /// assignment ;
     fn parse_assignment_stmt(&mut self) -> Result<(), CompilationError> {
-        let CodeReference::VarName(var_name) = self.parse_id_expr()?.code_ref else {
-            return Err(CompilationError::internal_error(
-                "Number was parsed as variable name".into(),
-            ));
-        };
-        self.match_tok(EQ_TOK)?; // =
-        let expr = self.parse_expression()?;
+        let generated_code = self.parse_assignment()?; // assignment
         self.match_tok(SEMIC_TOK)?; // ;
-        self.generated_code.push_str(
-            &self
-                .code_generator
-                .gen_assignment_stmt(&var_name, expr)
-                .map_err(|codegen_err| {
-                    CompilationError::codegen_error(
-                        self.last_seen_line,
-                        self.last_seen_column,
-                        codegen_err,
-                    )
-                })?,
-        );
+        self.push_generated_code(&generated_code);
         return Ok(());
     }
 
-    /// IF ( boolexpr ) stmt ELSE stmt
+    /// target = boolexpr | target = expression | target ASSIGNOP expression | target INCDEC
+    // Returns the code of the assignment, without pushing it.
+    // The value is computed once, before the target is accessed.
+    fn parse_assignment(&mut self) -> Result<String, CompilationError> {
+        let target = self.parse_target()?; // target
+        let lookahead_tok = self.lookahead_tok()?;
+        return match lookahead_tok {
+            EQ_TOK => {
+                self.match_tok(EQ_TOK)?; // =
+                let value = self.parse_value()?; // boolexpr | expression
+                let (mut generated_code, value) = self.split_value(value, &target);
+                generated_code.push_str(&self.gen_target_access(
+                    target,
+                    |code_generator, var_name| {
+                        code_generator.gen_assignment_stmt(var_name, value.result())
+                    },
+                )?);
+                Ok(generated_code)
+            }
+            ASSIGNOP_TOK => {
+                let assignop = self.match_tok(ASSIGNOP_TOK)?; // += | -= | *= | /=
+                let binop = BinaryOp::from_lexeme(Lexeme(assignop.0[..1].into()));
+                let value = self.parse_bitexpr()?; // bitexpr
+                let (mut generated_code, value) = self.split_value(value, &target);
+                generated_code.push_str(&self.gen_target_access(
+                    target,
+                    |code_generator, var_name| {
+                        code_generator.gen_compound_assignment_stmt(var_name, binop, value.result())
+                    },
+                )?);
+                Ok(generated_code)
+            }
+            INCDEC_TOK => {
+                let incdec = self.match_tok(INCDEC_TOK)?; // ++ | --
+                let binop = BinaryOp::from_lexeme(Lexeme(incdec.0[..1].into()));
+                self.gen_target_access(target, |code_generator, var_name| {
+                    code_generator.gen_increment_stmt(var_name, binop)
+                })
+            }
+            lookahead_tok => {
+                return Err(CompilationError::parsing_error(
+                    self.last_seen_line,
+                    self.last_seen_column,
+                    ParsingErrorKind::unexpected_tok(
+                        &[EQ_TOK, ASSIGNOP_TOK, INCDEC_TOK],
+                        lookahead_tok,
+                    ),
+                ))
+            }
+        };
+    }
+
+    // Split the value of an assignment into the code that computes it, and the value itself.
+    // An element at a dynamic index is assigned in one of many branches, so a negative literal is computed once beforehand.
+    fn split_value(&mut self, value: Expression, target: &Target) -> (String, Expression) {
+        let mut value = match target.index {
+            Some(_) => value.materialize(&mut self.code_generator),
+            None => value,
+        };
+        let generated_code = std::mem::take(&mut value.code_generated);
+        return (generated_code, value);
+    }
+
+    /// IF ( boolexpr ) stmt ELSE stmt | IF ( boolexpr ) stmt
     // *boolexpr code* (assume the result is stored in variable r)
     // JMPZ L1 r
     // *stmt if boolexpr is true*
@@ -425,86 +1274,604 @@ impl Parser {
     // *stmt if boolexpr is false*
     // L2: ("post label")
     // *after if statement*
+    //
+    // Without an else, there is nothing to jump over:
+    // *boolexpr code, jumping to L1 if it is false* (see Condition)
+    // *stmt if boolexpr is true*
+    // L1: ("else label")
+    // *after if statement*
     fn parse_if_stmt(&mut self) -> Result<(), CompilationError> {
+        return self.parse_if_chain(None);
+    }
+
+    /// IF ( boolexpr ) stmt ELSE if_stmt
+    // An if statement in the else of another if statement shares the post label of the first one,
+    // so every branch of the chain jumps directly to the end of the whole chain:
+    // *boolexpr 1 code, jumping to L1 if it is false*
+    // *stmt if boolexpr 1 is true*
+    // JUMP L2
+    // L1:
+    // *boolexpr 2 code, jumping to L3 if it is false*
+    // *stmt if boolexpr 2 is true*
+    // JUMP L2
+    // L3:
+    // *stmt if both are false*
+    // L2: ("post label" of the whole chain)
+    fn parse_if_chain(&mut self, chain_post_label: Option<Label>) -> Result<(), CompilationError> {
         self.match_tok(IF_TOK)?; // if
         self.match_tok(LPAREN_TOK)?; // (
-        let boolexpr = self.parse_boolexpr()?; // boolexpr
+        let condition = self.parse_condition()?; // boolexpr
         self.match_tok(RPAREN_TOK)?; // )
 
         let else_label = self.code_generator.new_label(); // request a new label for "else" from the code generator
-        let post_label = self.code_generator.new_label(); // request a new label for "post" from the code generator
+        let post_label = chain_post_label.unwrap_or_else(|| self.code_generator.new_label()); // request a new label for "post" from the code generator
 
-        self.push_generated_code(&boolexpr.code_generated); // boolexpr code
-        self.push_generated_code(&self.code_generator.gen_jump_if_false(else_label, boolexpr)); // Jump to else if false
+        let condition_code = condition.gen_jump_if_false(else_label, &mut self.code_generator);
+        self.push_generated_code(&condition_code); // Jump to else if false
         self.parse_stmt()?; // stmt
+        if !self.is_lookahead(ELSE_TOK) {
+            self.push_generated_code(&self.code_generator.gen_label_decleration(else_label)); // Declare else label
+            self.last_stmt_returns = false;
+            return Ok(());
+        }
+        let then_returns = self.last_stmt_returns;
         self.match_tok(ELSE_TOK)?; // else
         self.push_generated_code(&self.code_generator.gen_jump_to_label(post_label)); // Jump to post after stmt if true
         self.push_generated_code(&self.code_generator.gen_label_decleration(else_label)); // Declare else label
-        self.parse_stmt()?;
-        self.push_generated_code(&self.code_generator.gen_label_decleration(post_label)); // Declare post label
+        if self.is_lookahead(IF_TOK) {
+            self.parse_if_chain(Some(post_label))?; // else if
+        } else {
+            self.parse_stmt()?;
+        }
+        // The if statement returns only if both of its branches return
+        self.last_stmt_returns &= then_returns;
+        // Only the first if statement of the chain declares the post label
+        if chain_post_label.is_none() {
+            self.push_generated_code(&self.code_generator.gen_label_decleration(post_label));
+            // Declare post label
+        }
 
         return Ok(());
     }
 
     /// WHILE ( boolexpr ) stmt
     // L1:
-    // *boolexpr code* (assume the result is stored in variable r)
-    // JMPZ L2 r
+    // *boolexpr code, jumping to L2 if it is false* (see Condition)
     // *stmt code*
     // JUMP L1
     // L2:
-    fn parse_while_stmt(&mut self) -> Result<(), CompilationError> {
+    fn parse_while_stmt(
+        &mut self,
+        loop_name: Option<&'static str>,
+    ) -> Result<(), CompilationError> {
         self.match_tok(WHILE_TOK)?; // while
         self.match_tok(LPAREN_TOK)?; // (
-        let boolexpr = self.parse_boolexpr()?; // boolexpr
+        let condition = self.parse_condition()?; // boolexpr
         self.match_tok(RPAREN_TOK)?; // )
 
         let loop_label = self.code_generator.new_label(); // request a new label for the loop from the code generator
         let break_label = self.code_generator.new_label(); // request a new label for breaking from the loop from the code generator
 
         self.push_generated_code(&self.code_generator.gen_label_decleration(loop_label)); // L1:
-        self.push_generated_code(&boolexpr.code_generated); // code for the boolean expression
-        self.push_generated_code(&self.code_generator.gen_jump_if_false(break_label, boolexpr)); // JMPZ L2 r
-        self.parse_stmt()?;
+        let condition_code = condition.gen_jump_if_false(break_label, &mut self.code_generator);
+        self.push_generated_code(&condition_code); // Jump to L2 if false
+
+        // Inside of the loop, break jumps to L2 and continue jumps to L1
+        self.code_generator
+            .enter_control_context(ControlContext::Loop {
+                name: loop_name,
+                break_label,
+                continue_label: loop_label,
+            });
+        let stmt = self.parse_stmt();
+        self.code_generator.exit_control_context();
+        stmt?;
         self.push_generated_code(&self.code_generator.gen_jump_to_label(loop_label)); // JUMP L1
         self.push_generated_code(&self.code_generator.gen_label_decleration(break_label)); // L2:
 
         return Ok(());
     }
 
-    /// assignment_stmt | input_stmt | output_stmt | if_stmt | while_stmt | stmt_block
-    fn parse_stmt(&mut self) -> Result<(), CompilationError> {
+    /// DO stmt WHILE ( boolexpr ) ;
+    // L1: ("loop label")
+    // *stmt code*
+    // L2: ("continue label")
+    // *boolexpr code, jumping to L1 if it is true* (see Condition)
+    // L3: ("break label")
+    // JMPZ jumps if its operand is false, so the comparison that jumps back is negated with the opposite operator,
+    // like a < 5 to a > 4 (see BoolExpr::negated_relop), and only computed and negated with `not` when that's impossible.
+    fn parse_do_while_stmt(
+        &mut self,
+        loop_name: Option<&'static str>,
+    ) -> Result<(), CompilationError> {
+        self.match_tok(DO_TOK)?; // do
+
+        let loop_label = self.code_generator.new_label(); // request a new label for the loop from the code generator
+        let continue_label = self.code_generator.new_label(); // request a new label for the condition of the loop from the code generator
+        let break_label = self.code_generator.new_label(); // request a new label for breaking from the loop from the code generator
+
+        self.push_generated_code(&self.code_generator.gen_label_decleration(loop_label)); // L1:
+
+        // Inside of the loop, break jumps to L3 and continue jumps to L2
+        self.code_generator
+            .enter_control_context(ControlContext::Loop {
+                name: loop_name,
+                break_label,
+                continue_label,
+            });
+        let stmt = self.parse_stmt();
+        self.code_generator.exit_control_context();
+        stmt?;
+
+        self.match_tok(WHILE_TOK)?; // while
+        self.match_tok(LPAREN_TOK)?; // (
+        let condition = self.parse_condition()?; // boolexpr
+        self.match_tok(RPAREN_TOK)?; // )
+        self.match_tok(SEMIC_TOK)?; // ;
+
+        self.push_generated_code(&self.code_generator.gen_label_decleration(continue_label)); // L2:
+
+        // Loop back while the boolexpr is true
+        let condition_code = condition.gen_jump_if_true(loop_label, &mut self.code_generator);
+        self.push_generated_code(&condition_code); // Jump to L1 if true
+        self.push_generated_code(&self.code_generator.gen_label_decleration(break_label)); // L3:
+
+        return Ok(());
+    }
+
+    /// FOR ( assignment ; boolexpr ; assignment ) stmt
+    // *init assignment code*
+    // L1: ("loop label")
+    // *boolexpr code, jumping to L3 if it is false* (see Condition)
+    // *stmt code*
+    // L2: ("continue label")
+    // *increment assignment code*
+    // JUMP L1
+    // L3: ("break label")
+    fn parse_for_stmt(&mut self, loop_name: Option<&'static str>) -> Result<(), CompilationError> {
+        self.match_tok(FOR_TOK)?; // for
+        self.match_tok(LPAREN_TOK)?; // (
+        let init_code = self
+            .parse_assignment()
+            .map_err(|err| err.in_context("the initialization of a for loop"))?; // assignment
+        self.match_tok(SEMIC_TOK)?; // ;
+        let condition = self
+            .parse_condition()
+            .map_err(|err| err.in_context("the condition of a for loop"))?; // boolexpr
+        self.match_tok(SEMIC_TOK)?; // ;
+                                    // The increment is parsed before the stmt, but its code is only pushed after it.
+        let increment_code = self
+            .parse_assignment()
+            .map_err(|err| err.in_context("the increment of a for loop"))?; // assignment
+        self.match_tok(RPAREN_TOK)?; // )
+
+        let loop_label = self.code_generator.new_label(); // request a new label for the loop from the code generator
+        let continue_label = self.code_generator.new_label(); // request a new label for the increment from the code generator
+        let break_label = self.code_generator.new_label(); // request a new label for breaking from the loop from the code generator
+
+        self.push_generated_code(&init_code); // init assignment
+        self.push_generated_code(&self.code_generator.gen_label_decleration(loop_label)); // L1:
+        let condition_code = condition.gen_jump_if_false(break_label, &mut self.code_generator);
+        self.push_generated_code(&condition_code); // Jump to L3 if false
+
+        // Inside of the loop, break jumps to L3 and continue jumps to L2
+        self.code_generator
+            .enter_control_context(ControlContext::Loop {
+                name: loop_name,
+                break_label,
+                continue_label,
+            });
+        let stmt = self.parse_stmt();
+        self.code_generator.exit_control_context();
+        stmt?;
+        self.push_generated_code(&self.code_generator.gen_label_decleration(continue_label)); // L2:
+        self.push_generated_code(&increment_code); // increment assignment
+        self.push_generated_code(&self.code_generator.gen_jump_to_label(loop_label)); // JUMP L1
+        self.push_generated_code(&self.code_generator.gen_label_decleration(break_label)); // L3:
+
+        return Ok(());
+    }
+
+    /// SWITCH ( expression ) { caselist DEFAULT : stmtlist }
+    // *expression code* (assume the result is stored in variable r)
+    // JUMP L1
+    // L2: ("case label" of the first case)
+    // *first case stmtlist*
+    // L3: ("case label" of the second case, the first case falls through to it)
+    // *second case stmtlist*
+    // ...
+    // L4: ("default label")
+    // *default stmtlist*
+    // JUMP L5
+    // L1: ("test label")
+    // INQL t r c1
+    // JMPZ L2 t
+    // INQL t r c2
+    // JMPZ L3 t
+    // ...
+    // JUMP L4
+    // L5: ("post label")
+    fn parse_switch_stmt(&mut self) -> Result<(), CompilationError> {
+        self.match_tok(SWITCH_TOK)?; // switch
+        self.match_tok(LPAREN_TOK)?; // (
+        let expr = self.parse_expression()?; // expression
+        self.match_tok(RPAREN_TOK)?; // )
+        self.match_tok(LCURLY_TOK)?; // {
+        let expr = expr.materialize(&mut self.code_generator);
+
+        let test_label = self.code_generator.new_label(); // request a new label for the case tests from the code generator
+        let post_label = self.code_generator.new_label(); // request a new label for "post" from the code generator
+
+        self.push_generated_code(&expr.code_generated); // expression code
+        self.push_generated_code(&self.code_generator.gen_jump_to_label(test_label)); // JUMP L1
+
+        // Inside of the switch statement, break jumps to the post label
+        self.code_generator
+            .enter_control_context(ControlContext::Switch {
+                break_label: post_label,
+            });
+        let switch_body = self.parse_switch_body(expr.ty); // caselist DEFAULT : stmtlist
+        self.code_generator.exit_control_context();
+        let (cases, default_label) = switch_body?;
+
+        self.match_tok(RCURLY_TOK)?; // }
+        self.push_generated_code(&self.code_generator.gen_jump_to_label(post_label)); // Skip the case tests after the default stmtlist
+
+        self.push_generated_code(&self.code_generator.gen_label_decleration(test_label)); // Declare test label
+        for (case_ref, case_label) in cases {
+            let case_value = Expression {
+                ty: expr.ty,
+                code_ref: case_ref,
+                code_generated: String::new(),
+            }
+            .materialize(&mut self.code_generator);
+            self.push_generated_code(&case_value.code_generated); // A negative case is computed before it's compared
+            let case_test = self.code_generator.gen_jump_if_equal(
+                expr.ty,
+                case_label,
+                &expr.code_ref,
+                &case_value.code_ref,
+            );
+            self.push_generated_code(&case_test); // Jump to the case if it matches
+        }
+        self.push_generated_code(&self.code_generator.gen_jump_to_label(default_label)); // Jump to default if no case matched
+        self.push_generated_code(&self.code_generator.gen_label_decleration(post_label)); // Declare post label
+
+        return Ok(());
+    }
+
+    /// caselist DEFAULT : stmtlist
+    // Returns the label and value of every case, and the label of the default stmtlist
+    fn parse_switch_body(
+        &mut self,
+        ty: VarType,
+    ) -> Result<(Vec<(CodeReference, Label)>, Label), CompilationError> {
+        let mut cases: Vec<(CodeReference, Label)> = Vec::new();
+        while self.is_lookahead(CASE_TOK) {
+            self.match_tok(CASE_TOK)?; // case
+            let case_expr = self.parse_case_label()?; // expression
+            self.match_tok(COLON_TOK)?; // :
+            if case_expr.ty != ty {
+                return Err(CompilationError::codegen_error(
+                    self.last_seen_line,
+                    self.last_seen_column,
+                    CodeGenErrorKind::case_type_mismatch(ty, case_expr.code_ref, case_expr.ty),
+                ));
+            }
+            if cases
+                .iter()
+                .any(|(case_ref, _)| *case_ref == case_expr.code_ref)
+            {
+                return Err(CompilationError::codegen_error(
+                    self.last_seen_line,
+                    self.last_seen_column,
+                    CodeGenErrorKind::duplicate_case(case_expr.code_ref),
+                ));
+            }
+            let case_label = self.code_generator.new_label(); // request a new label for the case from the code generator
+            self.push_generated_code(&self.code_generator.gen_label_decleration(case_label)); // Declare case label
+            self.parse_stmtlist()?; // stmtlist
+            cases.push((case_expr.code_ref, case_label));
+        }
+
+        self.match_tok(DEFAULT_TOK)?; // default
+        self.match_tok(COLON_TOK)?; // :
+        let default_label = self.code_generator.new_label(); // request a new label for "default" from the code generator
+        self.push_generated_code(&self.code_generator.gen_label_decleration(default_label)); // Declare default label
+        self.parse_stmtlist()?; // stmtlist
+        return Ok((cases, default_label));
+    }
+
+    /// expression (computed at compile time)
+    // The value of a case label is computed like the value of a constant, so it can be negative or use a constant.
+    fn parse_case_label(&mut self) -> Result<Expression, CompilationError> {
+        self.code_generator.set_fold_constants(true);
+        let case_expr = self.parse_expression(); // expression
+        self.code_generator.set_fold_constants(false);
+        let case_expr = case_expr?;
+        self.code_generator
+            .check_constant("the case label", &case_expr)
+            .map_err(|codegen_err| {
+                CompilationError::codegen_error(
+                    self.last_seen_line,
+                    self.last_seen_column,
+                    codegen_err,
+                )
+            })?;
+        return Ok(case_expr);
+    }
+
+    /// ID : while_stmt | ID : do_while_stmt | ID : for_stmt
+    fn parse_labeled_loop(&mut self) -> Result<(), CompilationError> {
+        let loop_name: &'static str = Box::leak(self.parse_id()?.0); // ID
+        self.match_tok(COLON_TOK)?; // :
+        self.code_generator
+            .check_loop_name(loop_name)
+            .map_err(|codegen_err| {
+                CompilationError::codegen_error(
+                    self.last_seen_line,
+                    self.last_seen_column,
+                    codegen_err,
+                )
+            })?;
         let lookahead_tok = self.lookahead_tok()?;
         match lookahead_tok {
-            ID_TOK => return self.parse_assignment_stmt(),
-            INPUT_TOK => return self.parse_input_statement(),
-            OUTPUT_TOK => return self.parse_output_statement(),
-            WHILE_TOK => return self.parse_while_stmt(),
-            IF_TOK => return self.parse_if_stmt(),
-            LCURLY_TOK => return self.parse_stmt_block(),
+            WHILE_TOK => return self.parse_while_stmt(Some(loop_name)),
+            DO_TOK => return self.parse_do_while_stmt(Some(loop_name)),
+            FOR_TOK => return self.parse_for_stmt(Some(loop_name)),
             _ => {}
         }
         return Err(CompilationError::parsing_error(
             self.last_seen_line,
             self.last_seen_column,
-            ParsingErrorKind::unexpected_tok(
-                &[ID_TOK, INPUT_TOK, OUTPUT_TOK, IF_TOK, WHILE_TOK, LCURLY_TOK],
-                lookahead_tok,
-            ),
+            ParsingErrorKind::unexpected_tok(&[WHILE_TOK, DO_TOK, FOR_TOK], lookahead_tok),
         ));
     }
 
+    /// BREAK ; | BREAK ID ;
+    fn parse_break_stmt(&mut self) -> Result<(), CompilationError> {
+        self.match_tok(BREAK_TOK)?; // break
+        let loop_name = self.parse_id().ok().map(|id| id.0); // ID (optional)
+        self.match_tok(SEMIC_TOK)?; // ;
+        let generated_code = self
+            .code_generator
+            .gen_break_stmt(loop_name.as_deref())
+            .map_err(|codegen_err| {
+                CompilationError::codegen_error(
+                    self.last_seen_line,
+                    self.last_seen_column,
+                    codegen_err,
+                )
+            })?;
+        self.push_generated_code(&generated_code);
+        return Ok(());
+    }
+
+    /// EXIT ;
+    fn parse_exit_stmt(&mut self) -> Result<(), CompilationError> {
+        self.match_tok(EXIT_TOK)?; // exit
+        self.match_tok(SEMIC_TOK)?; // ;
+        let generated_code = self.code_generator.gen_exit_stmt();
+        self.push_generated_code(&generated_code);
+        return Ok(());
+    }
+
+    /// ASSERT ( boolexpr ) ;
+    fn parse_assert_stmt(&mut self) -> Result<(), CompilationError> {
+        self.match_tok(ASSERT_TOK)?; // assert
+        let line = self.last_seen_line;
+        self.match_tok(LPAREN_TOK)?; // (
+        let boolexpr = self.parse_boolexpr()?; // boolexpr
+        self.match_tok(RPAREN_TOK)?; // )
+        self.match_tok(SEMIC_TOK)?; // ;
+        let generated_code = self.code_generator.gen_assert_stmt(boolexpr, line);
+        self.push_generated_code(&generated_code);
+        return Ok(());
+    }
+
+    /// ASM { asm_lines }
+    // Every line of the block is a Quad instruction or the definition of a label, the block is compiled only if all of them are valid.
+    fn parse_asm_stmt(&mut self) -> Result<(), CompilationError> {
+        self.match_tok(ASM_TOK)?; // asm
+        self.match_tok(LCURLY_TOK)?; // {
+        let mut asm_block = AsmBlock::new();
+        let mut generated_code = String::new();
+        while !self.is_lookahead(RCURLY_TOK) {
+            let line = self.lookahead()?.line;
+            match self.parse_asm_line(&mut asm_block) {
+                Ok(line_code) => generated_code.push_str(&line_code), // asm_line
+                Err(error) => {
+                    // The lines are independent, so parsing continues from the next line
+                    self.errors_found.push(error);
+                    while self.is_asm_line(line) {
+                        self.ptr += 1;
+                    }
+                }
+            }
+        }
+        self.match_tok(RCURLY_TOK)?; // }
+
+        // An undefined label is reported where it's used
+        let undefined_labels = asm_block.finish();
+        if !undefined_labels.is_empty() {
+            self.errors_found.extend(undefined_labels);
+            return Ok(());
+        }
+        self.push_generated_code(&generated_code);
+        return Ok(());
+    }
+
+    /// ID : | ID asm_operands
+    // The operands of an instruction are the rest of its line.
+    fn parse_asm_line(&mut self, asm_block: &mut AsmBlock) -> Result<String, CompilationError> {
+        let line = self.lookahead()?.line;
+        let name = self.match_tok(ID_TOK)?.0; // ID
+        let generated_code = if self.is_asm_line(line) && self.is_lookahead(COLON_TOK) {
+            self.match_tok(COLON_TOK)?; // :
+            asm_block.gen_label_decleration(&name, &mut self.code_generator)
+        } else {
+            let mut operands = Vec::new();
+            while self.is_asm_line(line) {
+                operands.push(self.parse_asm_operand()?); // ID | NUM
+            }
+            asm_block.gen_instruction(&name, operands, &mut self.code_generator)
+        };
+        return generated_code.map_err(|codegen_err| {
+            CompilationError::codegen_error(self.last_seen_line, self.last_seen_column, codegen_err)
+        });
+    }
+
+    // Return true if the lookahead token is on the given line of an asm block
+    fn is_asm_line(&self, line: usize) -> bool {
+        return self.tokens.get(self.ptr).is_some_and(|lexed_token| {
+            lexed_token.line == line && lexed_token.token != RCURLY_TOK
+        });
+    }
+
+    /// ID | NUM | ADDOP NUM
+    // A negative literal is parsed so it can be reported by the asm block, Quad has no negative literals.
+    fn parse_asm_operand(&mut self) -> Result<AsmOperand, CompilationError> {
+        let lookahead = self.lookahead_tok()?;
+        match lookahead {
+            ID_TOK => {
+                let position = (self.last_seen_line, self.last_seen_column);
+                let name = self.match_tok(ID_TOK)?.0; // ID
+                return Ok(AsmOperand::Name(name, position));
+            }
+            NUM_TOK => {
+                return Ok(AsmOperand::Literal(self.parse_num_expr(false)?)); // {int / float literal}
+            }
+            ADDOP_TOK => {
+                let addop = self.match_tok(ADDOP_TOK)?; // + | -
+                let negative = BinaryOp::from_lexeme(addop) == BinaryOp::Sub;
+                return Ok(AsmOperand::Literal(self.parse_num_expr(negative)?)); // {int / float literal}
+            }
+            lookahead_tok => {
+                return Err(CompilationError::parsing_error(
+                    self.last_seen_line,
+                    self.last_seen_column,
+                    ParsingErrorKind::unexpected_tok(&[ID_TOK, NUM_TOK, ADDOP_TOK], lookahead_tok),
+                ))
+            }
+        }
+    }
+
+    /// CONTINUE ; | CONTINUE ID ;
+    fn parse_continue_stmt(&mut self) -> Result<(), CompilationError> {
+        self.match_tok(CONTINUE_TOK)?; // continue
+        let loop_name = self.parse_id().ok().map(|id| id.0); // ID (optional)
+        self.match_tok(SEMIC_TOK)?; // ;
+        let generated_code = self
+            .code_generator
+            .gen_continue_stmt(loop_name.as_deref())
+            .map_err(|codegen_err| {
+                CompilationError::codegen_error(
+                    self.last_seen_line,
+                    self.last_seen_column,
+                    codegen_err,
+                )
+            })?;
+        self.push_generated_code(&generated_code);
+        return Ok(());
+    }
+
+    /// assignment_stmt | input_stmt | output_stmt | if_stmt | labeled_loop | while_stmt | do_while_stmt | for_stmt | switch_stmt | break_stmt | continue_stmt | stmt_block
+    // Only a return statement, an if statement or a block can return on every path, any other statement clears `last_stmt_returns`.
+    fn parse_stmt(&mut self) -> Result<(), CompilationError> {
+        let lookahead_tok = self.lookahead_tok()?;
+        let stmt = match lookahead_tok {
+            ID_TOK if self.is_second_lookahead(COLON_TOK) => self.parse_labeled_loop(),
+            ID_TOK if self.is_second_lookahead(LPAREN_TOK) => self.parse_call_stmt(),
+            ID_TOK => self.parse_assignment_stmt(),
+            INPUT_TOK => self.parse_input_statement(),
+            OUTPUT_TOK => self.parse_output_statement(),
+            WHILE_TOK => self.parse_while_stmt(None),
+            DO_TOK => self.parse_do_while_stmt(None),
+            FOR_TOK => self.parse_for_stmt(None),
+            IF_TOK => self.parse_if_stmt(),
+            SWITCH_TOK => self.parse_switch_stmt(),
+            BREAK_TOK => self.parse_break_stmt(),
+            CONTINUE_TOK => self.parse_continue_stmt(),
+            RETURN_TOK => self.parse_return_stmt(),
+            EXIT_TOK => self.parse_exit_stmt(),
+            ASSERT_TOK => self.parse_assert_stmt(),
+            ASM_TOK => self.parse_asm_stmt(),
+            LCURLY_TOK => self.parse_stmt_block(),
+            _ => Err(CompilationError::parsing_error(
+                self.last_seen_line,
+                self.last_seen_column,
+                ParsingErrorKind::unexpected_tok(STMT_START_TOKENS, lookahead_tok),
+            )),
+        };
+        if ![RETURN_TOK, IF_TOK, LCURLY_TOK].contains(&lookahead_tok) {
+            self.last_stmt_returns = false;
+        }
+        return stmt;
+    }
+
     /// { stmtlist }
     fn parse_stmt_block(&mut self) -> Result<(), CompilationError> {
         self.match_tok(LCURLY_TOK)?; // {
+
+        // The names declared in the block can't be used after it, even if it failed to compile.
+        self.code_generator.enter_block();
+        let block = self.parse_block_rest();
+        self.code_generator.exit_block();
+        return block;
+    }
+
+    // The rest of a block after the {
+    // A block returns on every path if its last statement does.
+    fn parse_block_rest(&mut self) -> Result<(), CompilationError> {
+        self.last_stmt_returns = false;
+        self.parse_block_declerations()?; // declerations
         self.parse_stmtlist()?; // stmtlist
         self.match_tok(RCURLY_TOK)?; // }
         return Ok(());
     }
 
+    /// declerations decleration | epsilon
+    // Inside of a block, a declaration and a statement can both start with ID, so they're told apart by the tokens after it.
+    fn parse_block_declerations(&mut self) -> Result<(), CompilationError> {
+        if self.is_lookahead(CONST_TOK) {
+            self.parse_const_decleration()?;
+            return self.parse_block_declerations();
+        }
+        if self.is_decleration_start() {
+            self.parse_decleration()?;
+            return self.parse_block_declerations();
+        }
+        return Ok(());
+    }
+
+    // ID , | ID : type | ID [ NUM ] : | ID [ NUM ] ,
+    // A loop label (ID : while) isn't followed by a type.
+    fn is_decleration_start(&mut self) -> bool {
+        if !self.is_lookahead(ID_TOK) {
+            return false;
+        }
+        let is_type = |tok: Token| [INT_TOK, FLOAT_TOK, BOOL_TOK].contains(&tok);
+        return match self.nth_lookahead_tok(1) {
+            Some(tok) if tok == COMMA_TOK => true,
+            Some(tok) if tok == COLON_TOK => self.nth_lookahead_tok(2).is_some_and(is_type),
+            Some(tok) if tok == LBRACKET_TOK => {
+                self.nth_lookahead_tok(2) == Some(NUM_TOK)
+                    && self.nth_lookahead_tok(3) == Some(RBRACKET_TOK)
+                    && self
+                        .nth_lookahead_tok(4)
+                        .is_some_and(|tok| tok == COLON_TOK || tok == COMMA_TOK)
+            }
+            _ => false,
+        };
+    }
+
     /// stmt_list stmt | epsilon
     fn parse_stmtlist(&mut self) -> Result<(), CompilationError> {
-        if self.is_lookahead(RCURLY_TOK) {
+        // A stmtlist ends at the end of its block, or at the next case of a switch statement
+        if self.is_lookahead(RCURLY_TOK)
+            || self.is_lookahead(CASE_TOK)
+            || self.is_lookahead(DEFAULT_TOK)
+        {
             return Ok(());
         }
         let stmt = self.parse_stmt();
@@ -512,6 +1879,8 @@ impl Parser {
             if let Some(ptr_to_next_stmt) = self.try_find_next_stmt() {
                 self.ptr = ptr_to_next_stmt;
                 self.errors_found.push(error);
+                // The statement might have been a return, so a missing return isn't reported on top of its error
+                self.last_stmt_returns = true;
             } else {
                 return Err(error);
             }
@@ -519,11 +1888,12 @@ impl Parser {
         return self.parse_stmtlist();
     }
 
+    // Find the next statement, the end of the block, or the next case of a switch statement, to continue parsing from after an error.
     fn try_find_next_stmt(&self) -> Option<usize> {
         let mut ptr = self.ptr;
         while let Some(next_tok) = self.tokens.get(ptr) {
-            if [ID_TOK, INPUT_TOK, OUTPUT_TOK, IF_TOK, WHILE_TOK, LCURLY_TOK]
-                .contains(&next_tok.token)
+            if STMT_START_TOKENS.contains(&next_tok.token)
+                || [RCURLY_TOK, CASE_TOK, DEFAULT_TOK].contains(&next_tok.token)
             {
                 return Some(ptr);
             }
@@ -2,51 +2,74 @@
 
 use std::fmt::Display;
 pub const REGEX_TABLE: &[(RegexMatch, &str)] = &[
+    (RegexMatch::from_token_id(ASM_ID), r"^asm$"),
+    (RegexMatch::from_token_id(ASSERT_ID), r"^assert$"),
+    (RegexMatch::from_token_id(BOOL_ID), r"^bool$"),
     (RegexMatch::from_token_id(BREAK_ID), r"^break$"),
     (RegexMatch::from_token_id(CASE_ID), r"^case$"),
+    (RegexMatch::from_token_id(CONST_ID), r"^const$"),
+    (RegexMatch::from_token_id(CONTINUE_ID), r"^continue$"),
     (RegexMatch::from_token_id(DEFAULT_ID), r"^default$"),
+    (RegexMatch::from_token_id(DO_ID), r"^do$"),
     (RegexMatch::from_token_id(ELSE_ID), r"^else$"),
+    (RegexMatch::from_token_id(EXIT_ID), r"^exit$"),
+    (RegexMatch::from_token_id(FALSE_ID), r"^false$"),
     (RegexMatch::from_token_id(FLOAT_ID), r"^float$"),
+    (RegexMatch::from_token_id(FOR_ID), r"^for$"),
+    (RegexMatch::from_token_id(FUNC_ID), r"^func$"),
     (RegexMatch::from_token_id(IF_ID), r"^if$"),
     (RegexMatch::from_token_id(INPUT_ID), r"^input$"),
     (RegexMatch::from_token_id(INT_ID), r"^int$"),
     (RegexMatch::from_token_id(OUTPUT_ID), r"^output$"),
+    (RegexMatch::from_token_id(RETURN_ID), r"^return$"),
     (RegexMatch::from_token_id(SWITCH_ID), r"^switch$"),
+    (RegexMatch::from_token_id(TRUE_ID), r"^true$"),
     (RegexMatch::from_token_id(WHILE_ID), r"^while$"),
     (RegexMatch::from_token_id(RPAREN_ID), r"^\)$"),
     (RegexMatch::from_token_id(LPAREN_ID), r"^\($"),
     (RegexMatch::from_token_id(RCURLY_ID), r"^\}$"),
     (RegexMatch::from_token_id(LCURLY_ID), r"^\{$"),
+    (RegexMatch::from_token_id(RBRACKET_ID), r"^\]$"),
+    (RegexMatch::from_token_id(LBRACKET_ID), r"^\[$"),
     (RegexMatch::from_token_id(COMMA_ID), r"^,$"),
+    (RegexMatch::from_token_id(QUESTION_ID), r"^\?$"),
     (RegexMatch::from_token_id(COLON_ID), r"^:$"),
     (RegexMatch::from_token_id(SEMICOLON_ID), r"^;$"),
     (RegexMatch::from_token_id(EQUALS_ID), r"^=$"),
     (RegexMatch::from_token_id(RELOP_ID), r"^(==|!=|<|>|<=|>=)$"),
     (RegexMatch::from_token_id(ADDOP_ID), r"^(\+|-)$"),
-    (RegexMatch::from_token_id(MULOP_ID), r"^(\*|/)$"),
+    (RegexMatch::from_token_id(MULOP_ID), r"^(\*|/|%)$"),
     (RegexMatch::from_token_id(OR_ID), r"^\|\|$"),
     (RegexMatch::from_token_id(AND_ID), r"^&&$"),
     (RegexMatch::from_token_id(NOT_ID), r"^!$"),
+    (RegexMatch::from_token_id(BITOR_ID), r"^\|$"),
+    (RegexMatch::from_token_id(BITXOR_ID), r"^\^$"),
+    (RegexMatch::from_token_id(BITAND_ID), r"^&$"),
+    (RegexMatch::from_token_id(BITNOT_ID), r"^~$"),
+    (RegexMatch::from_token_id(SHIFTOP_ID), r"^(<<|>>)$"),
+    (RegexMatch::from_token_id(ASSIGNOP_ID), r"^(\+|-|\*|/)=$"),
+    (RegexMatch::from_token_id(INCDEC_ID), r"^(\+\+|--)$"),
     (
         RegexMatch::from_token_id(CAST_ID),
-        r"^static_cast<(int|float)>$",
+        r"^static_cast<(int|float|bool)>$",
     ),
     (
         RegexMatch::from_token_id(IDENT_ID),
         r"^[a-zA-Z][_a-zA-Z0-9]*$",
     ),
-    (RegexMatch::from_token_id(NUM_ID), r"^[0-9]+(\.[0-9]*)?$"),
+    // Like C's preprocessing numbers, anything that starts like a number is lexed as one, and checked by the parser.
+    (
+        RegexMatch::from_token_id(NUM_ID),
+        r"^\.?[0-9]([0-9a-zA-Z_.]|[eE][+-])*$",
+    ),
     (RegexMatch::NonToken(NonToken::Spaces), r"^[ \t]+$"),
     (RegexMatch::NonToken(NonToken::StartComment), r"^/\*$"),
     (RegexMatch::NonToken(NonToken::EndComment), r"^\*/$"),
+    (RegexMatch::NonToken(NonToken::LineComment), r"^//$"),
     (
         RegexMatch::NonToken(NonToken::Error(UNRECOGNIZED_TOKEN_ERR)),
         r"^.$",
     ),
-    (
-        RegexMatch::NonToken(NonToken::Error(UNRECOGNIZED_TOKEN_ERR)),
-        r"^[0-9]+(.[0-9]*)?[a-zA-Z]+$",
-    ),
 ];
 
 pub fn build_regex_set() -> regex::RegexSet {
@@ -83,6 +106,7 @@ pub enum NonToken {
     Spaces,
     StartComment,
     EndComment,
+    LineComment,
     Error(&'static str),
 }
 
@@ -145,6 +169,28 @@ const TOKEN_TABLE: &'static [Token] = &[
     Token::Operator(Operator::CAST),
     Token::Additional(Additional::Ident),
     Token::Additional(Additional::Num),
+    Token::Keyword(Keyword::Continue),
+    Token::Keyword(Keyword::Do),
+    Token::Keyword(Keyword::For),
+    Token::Keyword(Keyword::Bool),
+    Token::Keyword(Keyword::True),
+    Token::Keyword(Keyword::False),
+    Token::Keyword(Keyword::Exit),
+    Token::Keyword(Keyword::Assert),
+    Token::Operator(Operator::ASSIGNOP),
+    Token::Operator(Operator::INCDEC),
+    Token::Symbol(Symbol::RBracket),
+    Token::Symbol(Symbol::LBracket),
+    Token::Keyword(Keyword::Func),
+    Token::Keyword(Keyword::Return),
+    Token::Keyword(Keyword::Const),
+    Token::Symbol(Symbol::Question),
+    Token::Operator(Operator::BITOR),
+    Token::Operator(Operator::BITXOR),
+    Token::Operator(Operator::BITAND),
+    Token::Operator(Operator::BITNOT),
+    Token::Operator(Operator::SHIFTOP),
+    Token::Keyword(Keyword::Asm),
 ];
 
 pub const BREAK_ID: TokenID = 10;
@@ -175,6 +221,28 @@ pub const NOT_ID: TokenID = 34;
 pub const CAST_ID: TokenID = 35;
 pub const IDENT_ID: TokenID = 36;
 pub const NUM_ID: TokenID = 37;
+pub const CONTINUE_ID: TokenID = 38;
+pub const DO_ID: TokenID = 39;
+pub const FOR_ID: TokenID = 40;
+pub const BOOL_ID: TokenID = 41;
+pub const TRUE_ID: TokenID = 42;
+pub const FALSE_ID: TokenID = 43;
+pub const EXIT_ID: TokenID = 44;
+pub const ASSERT_ID: TokenID = 45;
+pub const ASSIGNOP_ID: TokenID = 46;
+pub const INCDEC_ID: TokenID = 47;
+pub const RBRACKET_ID: TokenID = 48;
+pub const LBRACKET_ID: TokenID = 49;
+pub const FUNC_ID: TokenID = 50;
+pub const RETURN_ID: TokenID = 51;
+pub const CONST_ID: TokenID = 52;
+pub const QUESTION_ID: TokenID = 53;
+pub const BITOR_ID: TokenID = 54;
+pub const BITXOR_ID: TokenID = 55;
+pub const BITAND_ID: TokenID = 56;
+pub const BITNOT_ID: TokenID = 57;
+pub const SHIFTOP_ID: TokenID = 58;
+pub const ASM_ID: TokenID = 59;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u16)]
@@ -190,6 +258,18 @@ pub enum Keyword {
     Output = OUTPUT_ID,
     Switch = SWITCH_ID,
     While = WHILE_ID,
+    Continue = CONTINUE_ID,
+    Do = DO_ID,
+    For = FOR_ID,
+    Bool = BOOL_ID,
+    True = TRUE_ID,
+    False = FALSE_ID,
+    Exit = EXIT_ID,
+    Assert = ASSERT_ID,
+    Func = FUNC_ID,
+    Return = RETURN_ID,
+    Const = CONST_ID,
+    Asm = ASM_ID,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -199,10 +279,13 @@ pub enum Symbol {
     LParen = LPAREN_ID,
     RCurly = RCURLY_ID,
     LCurly = LCURLY_ID,
+    RBracket = RBRACKET_ID,
+    LBracket = LBRACKET_ID,
     Comma = COMMA_ID,
     Colon = COLON_ID,
     SemiColon = SEMICOLON_ID,
     Equals = EQUALS_ID,
+    Question = QUESTION_ID,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -215,6 +298,13 @@ pub enum Operator {
     AND = AND_ID,
     NOT = NOT_ID,
     CAST = CAST_ID,
+    ASSIGNOP = ASSIGNOP_ID,
+    INCDEC = INCDEC_ID,
+    BITOR = BITOR_ID,
+    BITXOR = BITXOR_ID,
+    BITAND = BITAND_ID,
+    BITNOT = BITNOT_ID,
+    SHIFTOP = SHIFTOP_ID,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -229,9 +319,25 @@ pub const INPUT_TOK: Token = Token::Keyword(Keyword::Input);
 pub const OUTPUT_TOK: Token = Token::Keyword(Keyword::Output);
 pub const INT_TOK: Token = Token::Keyword(Keyword::Int);
 pub const FLOAT_TOK: Token = Token::Keyword(Keyword::Float);
+pub const BOOL_TOK: Token = Token::Keyword(Keyword::Bool);
+pub const TRUE_TOK: Token = Token::Keyword(Keyword::True);
+pub const FALSE_TOK: Token = Token::Keyword(Keyword::False);
 pub const IF_TOK: Token = Token::Keyword(Keyword::If);
 pub const ELSE_TOK: Token = Token::Keyword(Keyword::Else);
 pub const WHILE_TOK: Token = Token::Keyword(Keyword::While);
+pub const DO_TOK: Token = Token::Keyword(Keyword::Do);
+pub const FOR_TOK: Token = Token::Keyword(Keyword::For);
+pub const SWITCH_TOK: Token = Token::Keyword(Keyword::Switch);
+pub const CASE_TOK: Token = Token::Keyword(Keyword::Case);
+pub const DEFAULT_TOK: Token = Token::Keyword(Keyword::Default);
+pub const BREAK_TOK: Token = Token::Keyword(Keyword::Break);
+pub const CONTINUE_TOK: Token = Token::Keyword(Keyword::Continue);
+pub const EXIT_TOK: Token = Token::Keyword(Keyword::Exit);
+pub const ASSERT_TOK: Token = Token::Keyword(Keyword::Assert);
+pub const FUNC_TOK: Token = Token::Keyword(Keyword::Func);
+pub const RETURN_TOK: Token = Token::Keyword(Keyword::Return);
+pub const CONST_TOK: Token = Token::Keyword(Keyword::Const);
+pub const ASM_TOK: Token = Token::Keyword(Keyword::Asm);
 // OPERATOR
 pub const CAST_TOK: Token = Token::Operator(Operator::CAST);
 pub const MULOP_TOK: Token = Token::Operator(Operator::MULOP);
@@ -240,15 +346,25 @@ pub const OR_TOK: Token = Token::Operator(Operator::OR);
 pub const ADDOP_TOK: Token = Token::Operator(Operator::ADDOP);
 pub const NOT_TOK: Token = Token::Operator(Operator::NOT);
 pub const RELOP_TOK: Token = Token::Operator(Operator::RELOP);
+pub const ASSIGNOP_TOK: Token = Token::Operator(Operator::ASSIGNOP);
+pub const INCDEC_TOK: Token = Token::Operator(Operator::INCDEC);
+pub const BITOR_TOK: Token = Token::Operator(Operator::BITOR);
+pub const BITXOR_TOK: Token = Token::Operator(Operator::BITXOR);
+pub const BITAND_TOK: Token = Token::Operator(Operator::BITAND);
+pub const BITNOT_TOK: Token = Token::Operator(Operator::BITNOT);
+pub const SHIFTOP_TOK: Token = Token::Operator(Operator::SHIFTOP);
 // SYMBOLS
 pub const RPAREN_TOK: Token = Token::Symbol(Symbol::RParen);
 pub const LPAREN_TOK: Token = Token::Symbol(Symbol::LParen);
 pub const RCURLY_TOK: Token = Token::Symbol(Symbol::RCurly);
 pub const LCURLY_TOK: Token = Token::Symbol(Symbol::LCurly);
+pub const RBRACKET_TOK: Token = Token::Symbol(Symbol::RBracket);
+pub const LBRACKET_TOK: Token = Token::Symbol(Symbol::LBracket);
 pub const COLON_TOK: Token = Token::Symbol(Symbol::Colon);
 pub const COMMA_TOK: Token = Token::Symbol(Symbol::Comma);
 pub const SEMIC_TOK: Token = Token::Symbol(Symbol::SemiColon);
 pub const EQ_TOK: Token = Token::Symbol(Symbol::Equals);
+pub const QUESTION_TOK: Token = Token::Symbol(Symbol::Question);
 // ADDITIONAL
 pub const ID_TOK: Token = Token::Additional(Additional::Ident);
 pub const NUM_TOK: Token = Token::Additional(Additional::Num);